@@ -0,0 +1,344 @@
+use std::{
+    collections::VecDeque,
+    future::Future,
+    mem,
+    ops::ControlFlow::{self, Break, Continue},
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    task::{Context, Poll, Waker},
+};
+
+use crate::{async_fn::AsyncFnOnce1, Flow};
+
+pub const DEFAULT_CAPACITY: usize = 64;
+
+struct ChannelState<T> {
+    queue: VecDeque<T>,
+    capacity: usize,
+    closed: bool,
+    producers: usize,
+    receiver: Option<Waker>,
+    blocked: Vec<Waker>,
+    close_waiters: Vec<Waker>,
+}
+
+impl<T> ChannelState<T> {
+    fn wake_receiver(&mut self) {
+        if let Some(waker) = self.receiver.take() {
+            waker.wake();
+        }
+    }
+
+    fn try_push(&mut self, value: T) -> Result<(), T> {
+        // Without a buffer, a value is handed straight to a receiver waiting for one.
+        let room = match self.receiver {
+            Some(_) if self.capacity == 0 => 1,
+            _ => self.capacity,
+        };
+        if self.closed || self.queue.len() >= room {
+            return Err(value);
+        }
+        self.queue.push_back(value);
+        self.wake_receiver();
+        Ok(())
+    }
+
+    fn close(&mut self) {
+        self.closed = true;
+        self.wake_receiver();
+        mem::take(&mut self.blocked)
+            .into_iter()
+            .chain(mem::take(&mut self.close_waiters))
+            .for_each(Waker::wake);
+    }
+}
+
+type Channel<T> = Arc<Mutex<ChannelState<T>>>;
+
+fn lock<T>(channel: &Channel<T>) -> MutexGuard<'_, ChannelState<T>> {
+    channel.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// A cloneable handle for sending items into a [`ChannelFlow`] from any task or thread.
+pub struct ProducerHandle<T> {
+    channel: Channel<T>,
+}
+
+impl<T> ProducerHandle<T> {
+    /// Sends `value`, waiting for room in the buffer.
+    /// Resolves to `Err(value)` if the channel has been closed.
+    pub fn send(&self, value: T) -> ProducerSend<'_, T> {
+        ProducerSend {
+            producer: self,
+            value: Some(value),
+        }
+    }
+
+    /// Sends `value` without waiting, handing it back if the buffer is full or the channel is
+    /// closed.
+    pub fn try_send(&self, value: T) -> Result<(), T> {
+        lock(&self.channel).try_push(value)
+    }
+
+    /// Closes the channel for all producers. The flow returns once it has yielded the items
+    /// already sent.
+    pub fn close(&self) {
+        lock(&self.channel).close();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        lock(&self.channel).closed
+    }
+
+    /// Waits until the channel is closed, either by [`close`](Self::close) or because the flow
+    /// was dropped, then calls `on_close`.
+    ///
+    /// `on_close` also runs if this future is dropped first, so it's the place to unregister
+    /// callbacks when the flow is cancelled.
+    pub async fn await_close(&self, on_close: impl FnOnce()) {
+        struct OnDrop<F: FnOnce()>(Option<F>);
+
+        impl<F: FnOnce()> Drop for OnDrop<F> {
+            fn drop(&mut self) {
+                if let Some(f) = self.0.take() {
+                    f()
+                }
+            }
+        }
+
+        let _on_close = OnDrop(Some(on_close));
+
+        AwaitClose { producer: self }.await
+    }
+}
+
+impl<T> Clone for ProducerHandle<T> {
+    fn clone(&self) -> Self {
+        lock(&self.channel).producers += 1;
+        Self {
+            channel: self.channel.clone(),
+        }
+    }
+}
+
+impl<T> Drop for ProducerHandle<T> {
+    fn drop(&mut self) {
+        let mut channel = lock(&self.channel);
+        channel.producers -= 1;
+        if channel.producers == 0 {
+            channel.wake_receiver();
+        }
+    }
+}
+
+pub struct ProducerSend<'producer, T> {
+    producer: &'producer ProducerHandle<T>,
+    value: Option<T>,
+}
+
+impl<T> Unpin for ProducerSend<'_, T> {}
+
+impl<T> Future for ProducerSend<'_, T> {
+    type Output = Result<(), T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let Some(value) = this.value.take() else {
+            return Poll::Ready(Ok(()));
+        };
+
+        let mut channel = lock(&this.producer.channel);
+
+        match channel.try_push(value) {
+            Ok(()) => Poll::Ready(Ok(())),
+            Err(value) if channel.closed => Poll::Ready(Err(value)),
+            Err(value) => {
+                channel.blocked.push(cx.waker().clone());
+                this.value = Some(value);
+                Poll::Pending
+            }
+        }
+    }
+}
+
+struct AwaitClose<'producer, T> {
+    producer: &'producer ProducerHandle<T>,
+}
+
+impl<T> Future for AwaitClose<'_, T> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut channel = lock(&self.producer.channel);
+        if channel.closed {
+            Poll::Ready(())
+        } else {
+            channel.close_waiters.push(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+struct ChannelReceiver<T> {
+    channel: Channel<T>,
+}
+
+impl<T> ChannelReceiver<T> {
+    fn poll_recv(&self, cx: &mut Context) -> Poll<Option<T>> {
+        let mut channel = lock(&self.channel);
+
+        if let Some(value) = channel.queue.pop_front() {
+            mem::take(&mut channel.blocked)
+                .into_iter()
+                .for_each(Waker::wake);
+            Poll::Ready(Some(value))
+        } else if channel.closed || channel.producers == 0 {
+            Poll::Ready(None)
+        } else {
+            channel.receiver = Some(cx.waker().clone());
+            if channel.capacity == 0 {
+                mem::take(&mut channel.blocked)
+                    .into_iter()
+                    .for_each(Waker::wake);
+            }
+            Poll::Pending
+        }
+    }
+}
+
+impl<T> Drop for ChannelReceiver<T> {
+    fn drop(&mut self) {
+        let mut channel = lock(&self.channel);
+        channel.queue.clear();
+        channel.close();
+    }
+}
+
+pin_project_lite::pin_project!(
+    #[project = ChannelFlowBodyProj]
+    enum ChannelFlowBody<T, Fun: AsyncFnOnce1<ProducerHandle<T>>> {
+        Fun {
+            fun: Option<Fun>,
+        },
+        Fut {
+            #[pin]
+            fut: Fun::Fut,
+        },
+        Done,
+    }
+);
+
+pin_project_lite::pin_project!(
+    /// A flow that yields the items sent by the [`ProducerHandle`]s of its body.
+    pub struct ChannelFlow<T, Fun: AsyncFnOnce1<ProducerHandle<T>>> {
+        #[pin]
+        body: ChannelFlowBody<T, Fun>,
+        receiver: ChannelReceiver<T>,
+        ret: Option<Fun::Output>,
+        close_on_return: bool,
+    }
+);
+
+impl<T, Fun> Flow for ChannelFlow<T, Fun>
+where
+    Fun: AsyncFnOnce1<ProducerHandle<T>>,
+{
+    type Yield = T;
+    type Return = Fun::Output;
+
+    fn poll_resume(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        input: &mut Option<()>,
+    ) -> Poll<ControlFlow<Self::Return, Self::Yield>> {
+        let mut this = self.project();
+
+        if let ChannelFlowBodyProj::Fun { fun } = this.body.as_mut().project() {
+            if input.is_none() {
+                return Poll::Pending;
+            }
+            let producer = ProducerHandle {
+                channel: this.receiver.channel.clone(),
+            };
+            let fut = fun.take().unwrap().call_once(producer);
+            this.body.set(ChannelFlowBody::Fut { fut });
+        }
+
+        if let ChannelFlowBodyProj::Fut { fut } = this.body.as_mut().project() {
+            if let Poll::Ready(ret) = fut.poll(cx) {
+                this.body.set(ChannelFlowBody::Done);
+                *this.ret = Some(ret);
+
+                if *this.close_on_return {
+                    lock(&this.receiver.channel).close();
+                }
+            }
+        }
+
+        if input.is_none() {
+            return Poll::Pending;
+        }
+
+        match this.receiver.poll_recv(cx) {
+            Poll::Ready(Some(value)) => {
+                *input = None;
+                Poll::Ready(Continue(value))
+            }
+            Poll::Ready(None) => match this.ret.take() {
+                Some(ret) => {
+                    *input = None;
+                    Poll::Ready(Break(ret))
+                }
+                None => Poll::Pending,
+            },
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+fn new_channel_flow<T, Fun>(capacity: usize, fun: Fun, close_on_return: bool) -> ChannelFlow<T, Fun>
+where
+    Fun: AsyncFnOnce1<ProducerHandle<T>>,
+{
+    ChannelFlow {
+        body: ChannelFlowBody::Fun { fun: Some(fun) },
+        receiver: ChannelReceiver {
+            channel: Arc::new(Mutex::new(ChannelState {
+                queue: VecDeque::with_capacity(capacity),
+                capacity,
+                closed: false,
+                producers: 1,
+                receiver: None,
+                blocked: Vec::new(),
+                close_waiters: Vec::new(),
+            })),
+        },
+        ret: None,
+        close_on_return,
+    }
+}
+
+/// Creates a flow from a body that sends items through a [`ProducerHandle`] buffering up to
+/// `capacity` items.
+///
+/// With a capacity of zero the channel is a rendezvous: a send only goes through while the flow
+/// is waiting for its next item.
+///
+/// The flow returns the body's output once the body has completed and every handle has been
+/// dropped or the channel has been closed.
+pub fn channel_flow_from_fn<T, Fun>(capacity: usize, fun: Fun) -> ChannelFlow<T, Fun>
+where
+    Fun: AsyncFnOnce1<ProducerHandle<T>>,
+{
+    new_channel_flow(capacity, fun, false)
+}
+
+/// Like [`channel_flow_from_fn`], but the channel is closed when the body returns, so the body
+/// should end with [`await_close`](ProducerHandle::await_close) to keep callbacks registered
+/// until the flow is closed or dropped. Items sent before the body returned are still yielded.
+pub fn callback_flow_from_fn<T, Fun>(capacity: usize, fun: Fun) -> ChannelFlow<T, Fun>
+where
+    Fun: AsyncFnOnce1<ProducerHandle<T>>,
+{
+    new_channel_flow(capacity, fun, true)
+}
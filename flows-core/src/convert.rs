@@ -1,13 +1,20 @@
 use std::{
+    error::Error,
+    fmt,
     future::Future,
-    mem,
-    ops::ControlFlow::{self, Break, Continue},
+    io, mem,
+    ops::{
+        ControlFlow::{self, Break, Continue},
+        Deref,
+    },
     pin::Pin,
+    sync::{mpsc, Arc, Condvar, Mutex, MutexGuard, PoisonError},
     task::{self, Context, Poll},
+    thread,
 };
 
 use either::Either;
-use futures_util::{self, Sink, Stream};
+use futures_util::{self, task::AtomicWaker, Sink, Stream};
 
 use super::Flow;
 use crate::my_try::MyTry;
@@ -35,6 +42,7 @@ where
 }
 
 pin_project_lite::pin_project!(
+    /// Yields the items of any [`Stream`], `Result`s or not, one per resume.
     pub struct StreamFlow<St: ?Sized> {
         #[pin]
         inner: St,
@@ -66,7 +74,7 @@ where
 
 impl<St> IntoFlow<(), StreamFlow<()>> for St
 where
-    St: futures_util::stream::TryStream,
+    St: Stream,
 {
     type Yield = St::Item;
     type Return = ();
@@ -124,6 +132,135 @@ where
     }
 }
 
+struct ReceiverState<T> {
+    demand: bool,
+    item: Option<T>,
+    done: bool,
+}
+
+struct ReceiverShared<T> {
+    state: Mutex<ReceiverState<T>>,
+    demanded: Condvar,
+    waker: AtomicWaker,
+}
+
+impl<T> ReceiverShared<T> {
+    fn lock(&self) -> MutexGuard<'_, ReceiverState<T>> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Yields the messages of a [`mpsc::Receiver`], blocking on it from a separate thread.
+///
+/// The thread is spawned on the first resume and only receives a message once one is
+/// demanded. It exits once the channel disconnects, or once the flow is dropped. A message
+/// demanded before the drop is still taken off the channel when it arrives, and dropped.
+///
+/// `Receiver` converts into this flow through `IntoFlow<(), ReceiverFlow<()>>`. It's also
+/// `IntoIterator`, so a bare `into_flow()` can't tell the two apart and needs the marker, as in
+/// `IntoFlow::<(), ReceiverFlow<()>>::into_flow(rx)`, or [`ReceiverFlow::new`]. Without it the
+/// call fails to compile rather than blocking the executor on the iterator.
+pub struct ReceiverFlow<T> {
+    receiver: Option<mpsc::Receiver<T>>,
+    shared: Arc<ReceiverShared<T>>,
+}
+
+impl<T> Unpin for ReceiverFlow<T> {}
+
+impl<T: Send + 'static> Flow for ReceiverFlow<T> {
+    type Yield = T;
+    type Return = ();
+
+    fn poll_resume(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        input: &mut Option<()>,
+    ) -> Poll<ControlFlow<Self::Return, Self::Yield>> {
+        if input.is_none() {
+            return Poll::Pending;
+        }
+
+        let this = self.get_mut();
+        if let Some(receiver) = this.receiver.take() {
+            let shared = this.shared.clone();
+            thread::spawn(move || receive(receiver, shared));
+        }
+
+        this.shared.waker.register(cx.waker());
+
+        let mut state = this.shared.lock();
+        if let Some(item) = state.item.take() {
+            *input = None;
+            Poll::Ready(Continue(item))
+        } else if state.done {
+            *input = None;
+            Poll::Ready(Break(()))
+        } else {
+            state.demand = true;
+            this.shared.demanded.notify_one();
+            Poll::Pending
+        }
+    }
+}
+
+impl<T> Drop for ReceiverFlow<T> {
+    fn drop(&mut self) {
+        self.shared.lock().done = true;
+        self.shared.demanded.notify_one();
+    }
+}
+
+fn receive<T>(receiver: mpsc::Receiver<T>, shared: Arc<ReceiverShared<T>>) {
+    loop {
+        let mut state = shared
+            .demanded
+            .wait_while(shared.lock(), |state| !state.demand && !state.done)
+            .unwrap_or_else(PoisonError::into_inner);
+        if state.done {
+            return;
+        }
+        state.demand = false;
+        drop(state);
+
+        let received = receiver.recv();
+
+        let mut state = shared.lock();
+        match received {
+            Ok(item) => state.item = Some(item),
+            Err(_) => state.done = true,
+        }
+        drop(state);
+        shared.waker.wake();
+    }
+}
+
+impl<T: Send + 'static> IntoFlow<(), ReceiverFlow<()>> for mpsc::Receiver<T> {
+    type Yield = T;
+    type Return = ();
+    type IntoFlow = ReceiverFlow<T>;
+
+    fn into_flow(self) -> Self::IntoFlow {
+        ReceiverFlow::new(self)
+    }
+}
+
+impl<T> ReceiverFlow<T> {
+    pub fn new(receiver: mpsc::Receiver<T>) -> Self {
+        ReceiverFlow {
+            receiver: Some(receiver),
+            shared: Arc::new(ReceiverShared {
+                state: Mutex::new(ReceiverState {
+                    demand: false,
+                    item: None,
+                    done: false,
+                }),
+                demanded: Condvar::new(),
+                waker: AtomicWaker::new(),
+            }),
+        }
+    }
+}
+
 pub(crate) enum SinkFlowState {
     Ready,
     Pending,
@@ -238,6 +375,326 @@ where
     }
 }
 
+pin_project_lite::pin_project!(
+    /// Collects `Ok`/`Some` items into `Dest`, stopping the source at the first `Err`/`None`.
+    pub struct TryExtendFromFlow<Dest = (), Src = (), Res = ()> {
+        #[pin]
+        src: Src,
+        dest: Option<Dest>,
+        input: Option<Res>,
+    }
+);
+
+impl<I, Src, Dest, Res> Future for TryExtendFromFlow<Dest, Src, Res>
+where
+    Src: Flow<Res, Yield = I>,
+    I: MyTry,
+    Dest: Extend<I::Continue>,
+    Res: Default,
+{
+    type Output = I::Mapped<Dest>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        loop {
+            let this = self.as_mut().project();
+
+            match task::ready!(this.src.poll_resume(cx, this.input)) {
+                Continue(item) => match item.into_control_flow() {
+                    Continue(item) => {
+                        this.dest.as_mut().unwrap().extend([item]);
+                        this.input.get_or_insert_with(Res::default);
+                    }
+                    Break(residual) => return Poll::Ready(MyTry::from_break(residual)),
+                },
+                Break(_) => return Poll::Ready(MyTry::from_continue(this.dest.take().unwrap())),
+            }
+        }
+    }
+}
+
+impl<T, E, Res, Dest> FromFlow<Result<T, E>, Res, TryExtendFromFlow> for Result<Dest, E>
+where
+    Dest: Default + Extend<T>,
+    Res: Default,
+{
+    type FromFlowFuture<Fl: Flow<Res, Yield = Result<T, E>>> = TryExtendFromFlow<Dest, Fl, Res>;
+
+    fn from_flow<Fl: Flow<Res, Yield = Result<T, E>>>(src: Fl) -> Self::FromFlowFuture<Fl> {
+        TryExtendFromFlow {
+            src,
+            dest: Some(Dest::default()),
+            input: Some(Res::default()),
+        }
+    }
+}
+
+impl<T, Res, Dest> FromFlow<Option<T>, Res, TryExtendFromFlow> for Option<Dest>
+where
+    Dest: Default + Extend<T>,
+    Res: Default,
+{
+    type FromFlowFuture<Fl: Flow<Res, Yield = Option<T>>> = TryExtendFromFlow<Dest, Fl, Res>;
+
+    fn from_flow<Fl: Flow<Res, Yield = Option<T>>>(src: Fl) -> Self::FromFlowFuture<Fl> {
+        TryExtendFromFlow {
+            src,
+            dest: Some(Dest::default()),
+            input: Some(Res::default()),
+        }
+    }
+}
+
+/// Like [`Extend`], but for destinations that can refuse an item.
+pub trait TryExtend<T> {
+    type Error;
+
+    fn try_extend_one(&mut self, item: T) -> Result<(), Self::Error>;
+}
+
+/// Writes byte-slice items into an [`io::Write`], such as a file or socket.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WriteInto<W>(pub W);
+
+impl<W> WriteInto<W> {
+    pub fn into_inner(self) -> W {
+        self.0
+    }
+}
+
+impl<W: io::Write, B: AsRef<[u8]>> TryExtend<B> for WriteInto<W> {
+    type Error = io::Error;
+
+    fn try_extend_one(&mut self, item: B) -> Result<(), Self::Error> {
+        self.0.write_all(item.as_ref())
+    }
+}
+
+/// The item that didn't fit into a [`Bounded`] collection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CapacityError<T>(pub T);
+
+impl<T> fmt::Display for CapacityError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("collection is at capacity")
+    }
+}
+
+impl<T: fmt::Debug> Error for CapacityError<T> {}
+
+/// A collection that accepts at most `N` items, failing with [`CapacityError`] after that.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Bounded<C, const N: usize> {
+    inner: C,
+    len: usize,
+}
+
+impl<C, const N: usize> Bounded<C, N> {
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<C, const N: usize> Deref for Bounded<C, N> {
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        &self.inner
+    }
+}
+
+impl<T, C: Extend<T>, const N: usize> TryExtend<T> for Bounded<C, N> {
+    type Error = CapacityError<T>;
+
+    fn try_extend_one(&mut self, item: T) -> Result<(), Self::Error> {
+        if self.len == N {
+            return Err(CapacityError(item));
+        }
+
+        self.inner.extend([item]);
+        self.len += 1;
+        Ok(())
+    }
+}
+
+/// Fallible counterpart of [`FromFlow`], stopping the source at the first failure.
+pub trait TryFromFlow<Item, Resume = (), M = ()>: Sized {
+    type Error;
+    type TryFromFlowFuture<Fl: Flow<Resume, Yield = Item>>: Future<
+        Output = Result<Self, Self::Error>,
+    >;
+
+    fn try_from_flow<Fl: Flow<Resume, Yield = Item>>(src: Fl) -> Self::TryFromFlowFuture<Fl>;
+}
+
+pin_project_lite::pin_project!(
+    pub struct TryCollect<Dest = (), Src = (), Res = ()> {
+        #[pin]
+        src: Src,
+        dest: Option<Dest>,
+        input: Option<Res>,
+    }
+);
+
+impl<T, Src, Dest, Res> Future for TryCollect<Dest, Src, Res>
+where
+    Src: Flow<Res, Yield = T>,
+    Dest: TryExtend<T>,
+    Res: Default,
+{
+    type Output = Result<Dest, Dest::Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        loop {
+            let this = self.as_mut().project();
+
+            match task::ready!(this.src.poll_resume(cx, this.input)) {
+                Continue(item) => {
+                    this.dest.as_mut().unwrap().try_extend_one(item)?;
+                    this.input.get_or_insert_with(Res::default);
+                }
+                Break(_) => return Poll::Ready(Ok(this.dest.take().unwrap())),
+            }
+        }
+    }
+}
+
+impl<T, Res, Dest> TryFromFlow<T, Res, TryCollect> for Dest
+where
+    Dest: Default + TryExtend<T>,
+    Res: Default,
+{
+    type Error = Dest::Error;
+    type TryFromFlowFuture<Fl: Flow<Res, Yield = T>> = TryCollect<Dest, Fl, Res>;
+
+    fn try_from_flow<Fl: Flow<Res, Yield = T>>(src: Fl) -> Self::TryFromFlowFuture<Fl> {
+        try_collect_into(src, Dest::default())
+    }
+}
+
+pin_project_lite::pin_project!(
+    /// Like [`TryCollect`], but for `Ok`/`Some` items, also stopping the source at the first
+    /// `Err`/`None`, as `try_for_each!` does.
+    pub struct TryCollectItems<Dest = (), Src = (), Res = ()> {
+        #[pin]
+        src: Src,
+        dest: Option<Dest>,
+        input: Option<Res>,
+    }
+);
+
+impl<I, Src, Dest, Res> Future for TryCollectItems<Dest, Src, Res>
+where
+    Src: Flow<Res, Yield = I>,
+    I: MyTry,
+    Dest: TryExtend<I::Continue>,
+    Res: Default,
+{
+    type Output = Result<I::Mapped<Dest>, Dest::Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        loop {
+            let this = self.as_mut().project();
+
+            match task::ready!(this.src.poll_resume(cx, this.input)) {
+                Continue(item) => match item.into_control_flow() {
+                    Continue(item) => {
+                        this.dest.as_mut().unwrap().try_extend_one(item)?;
+                        this.input.get_or_insert_with(Res::default);
+                    }
+                    Break(residual) => return Poll::Ready(Ok(MyTry::from_break(residual))),
+                },
+                Break(_) => {
+                    return Poll::Ready(Ok(MyTry::from_continue(this.dest.take().unwrap())))
+                }
+            }
+        }
+    }
+}
+
+impl<T, E, Res, Dest> TryFromFlow<Result<T, E>, Res, TryCollectItems> for Result<Dest, E>
+where
+    Dest: Default + TryExtend<T>,
+    Res: Default,
+{
+    type Error = Dest::Error;
+    type TryFromFlowFuture<Fl: Flow<Res, Yield = Result<T, E>>> = TryCollectItems<Dest, Fl, Res>;
+
+    fn try_from_flow<Fl: Flow<Res, Yield = Result<T, E>>>(src: Fl) -> Self::TryFromFlowFuture<Fl> {
+        TryCollectItems {
+            src,
+            dest: Some(Dest::default()),
+            input: Some(Res::default()),
+        }
+    }
+}
+
+impl<T, Res, Dest> TryFromFlow<Option<T>, Res, TryCollectItems> for Option<Dest>
+where
+    Dest: Default + TryExtend<T>,
+    Res: Default,
+{
+    type Error = Dest::Error;
+    type TryFromFlowFuture<Fl: Flow<Res, Yield = Option<T>>> = TryCollectItems<Dest, Fl, Res>;
+
+    fn try_from_flow<Fl: Flow<Res, Yield = Option<T>>>(src: Fl) -> Self::TryFromFlowFuture<Fl> {
+        TryCollectItems {
+            src,
+            dest: Some(Dest::default()),
+            input: Some(Res::default()),
+        }
+    }
+}
+
+/// Feeds the items into an existing `dest`, such as a file or socket wrapped in [`WriteInto`].
+pub fn try_collect_into<T, Res, Src, Dest>(src: Src, dest: Dest) -> TryCollect<Dest, Src, Res>
+where
+    Src: Flow<Res, Yield = T>,
+    Dest: TryExtend<T>,
+    Res: Default,
+{
+    TryCollect {
+        src,
+        dest: Some(dest),
+        input: Some(Res::default()),
+    }
+}
+
+pin_project_lite::pin_project!(
+    /// Stores the inner flow's return in `ret`, so it survives being handed to [`FromFlow`].
+    pub(crate) struct StoreReturn<'ret, Fl, Ret> {
+        #[pin]
+        pub(crate) flow: Fl,
+        pub(crate) ret: &'ret mut Option<Ret>,
+    }
+);
+
+impl<Fl, Res> Flow<Res> for StoreReturn<'_, Fl, Fl::Return>
+where
+    Fl: Flow<Res>,
+{
+    type Yield = Fl::Yield;
+    type Return = ();
+
+    fn poll_resume(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        input: &mut Option<Res>,
+    ) -> Poll<ControlFlow<Self::Return, Self::Yield>> {
+        let this = self.project();
+        this.flow.poll_resume(cx, input).map(|ctrl| match ctrl {
+            Continue(item) => Continue(item),
+            Break(ret) => {
+                **this.ret = Some(ret);
+                Break(())
+            }
+        })
+    }
+
+    fn can_resume(self: Pin<&mut Self>) -> bool {
+        self.project().flow.can_resume()
+    }
+}
+
 pin_project_lite::pin_project!(
     pub struct EitherFlow<L, R> {
         #[pin]
@@ -281,4 +738,11 @@ where
             Either::Right(x) => x.poll_resume(cx, input),
         }
     }
+
+    fn can_resume(self: Pin<&mut Self>) -> bool {
+        match self.project().inner.as_pin_mut() {
+            Either::Left(x) => x.can_resume(),
+            Either::Right(x) => x.can_resume(),
+        }
+    }
 }
@@ -0,0 +1,171 @@
+use std::{
+    io,
+    ops::ControlFlow::{self, Break, Continue},
+    pin::Pin,
+    task::{self, Context, Poll},
+};
+
+use futures_util::io::{AsyncBufRead, AsyncRead};
+
+use super::Flow;
+
+pin_project_lite::pin_project!(
+    /// Yields the bytes of an [`AsyncRead`] in chunks; see [`read_chunks`].
+    pub struct ReadChunks<R> {
+        #[pin]
+        reader: R,
+        buf_size: usize,
+        buf: Option<Vec<u8>>,
+    }
+);
+
+impl<R> Flow<Option<Vec<u8>>> for ReadChunks<R>
+where
+    R: AsyncRead,
+{
+    type Yield = Vec<u8>;
+    type Return = io::Result<()>;
+
+    fn poll_resume(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        input: &mut Option<Option<Vec<u8>>>,
+    ) -> Poll<ControlFlow<Self::Return, Self::Yield>> {
+        let mut this = self.project();
+
+        if let Some(resumed) = input.take() {
+            let mut buf = resumed.unwrap_or_default();
+            buf.clear();
+            buf.resize(*this.buf_size, 0);
+            *this.buf = Some(buf);
+        }
+
+        let Some(buf) = this.buf.as_mut() else {
+            return Poll::Pending;
+        };
+
+        loop {
+            match task::ready!(this.reader.as_mut().poll_read(cx, buf)) {
+                Ok(0) => {
+                    *this.buf = None;
+                    return Poll::Ready(Break(Ok(())));
+                }
+                Ok(read) => {
+                    let mut buf = this.buf.take().unwrap();
+                    buf.truncate(read);
+                    return Poll::Ready(Continue(buf));
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => {
+                    *this.buf = None;
+                    return Poll::Ready(Break(Err(err)));
+                }
+            }
+        }
+    }
+}
+
+/// Yields what `reader` reads, in chunks of at most `buf_size` bytes, then returns the
+/// first error or `Ok(())` at the end of the input.
+///
+/// Resuming with `Some(buf)` reads the next chunk into `buf`, so a consumer can hand
+/// back chunks it's done with; resuming with `None` allocates a new one.
+///
+/// # Panics
+///
+/// Panics if `buf_size` is 0.
+pub fn read_chunks<R: AsyncRead>(reader: R, buf_size: usize) -> ReadChunks<R> {
+    assert!(buf_size != 0, "buf_size must be non-zero");
+    ReadChunks {
+        reader,
+        buf_size,
+        buf: None,
+    }
+}
+
+pin_project_lite::pin_project!(
+    /// Yields the lines of an [`AsyncBufRead`]; see [`read_lines`].
+    pub struct ReadLines<R> {
+        #[pin]
+        reader: R,
+        line: Option<Vec<u8>>,
+    }
+);
+
+fn finish_line(mut line: Vec<u8>) -> ControlFlow<io::Result<()>, String> {
+    if line.ends_with(b"\r") {
+        line.pop();
+    }
+    match String::from_utf8(line) {
+        Ok(line) => Continue(line),
+        Err(err) => Break(Err(io::Error::new(io::ErrorKind::InvalidData, err))),
+    }
+}
+
+impl<R> Flow<Option<String>> for ReadLines<R>
+where
+    R: AsyncBufRead,
+{
+    type Yield = String;
+    type Return = io::Result<()>;
+
+    fn poll_resume(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        input: &mut Option<Option<String>>,
+    ) -> Poll<ControlFlow<Self::Return, Self::Yield>> {
+        let mut this = self.project();
+
+        if let Some(resumed) = input.take() {
+            let mut line = resumed.unwrap_or_default().into_bytes();
+            line.clear();
+            *this.line = Some(line);
+        }
+
+        let Some(line) = this.line.as_mut() else {
+            return Poll::Pending;
+        };
+
+        loop {
+            let available = match task::ready!(this.reader.as_mut().poll_fill_buf(cx)) {
+                Ok(available) => available,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => {
+                    *this.line = None;
+                    return Poll::Ready(Break(Err(err)));
+                }
+            };
+
+            if available.is_empty() {
+                let line = this.line.take().unwrap();
+                return Poll::Ready(match line.is_empty() {
+                    true => Break(Ok(())),
+                    false => finish_line(line),
+                });
+            }
+
+            match available.iter().position(|&byte| byte == b'\n') {
+                Some(end) => {
+                    line.extend_from_slice(&available[..end]);
+                    this.reader.as_mut().consume(end + 1);
+                    return Poll::Ready(finish_line(this.line.take().unwrap()));
+                }
+                None => {
+                    let read = available.len();
+                    line.extend_from_slice(available);
+                    this.reader.as_mut().consume(read);
+                }
+            }
+        }
+    }
+}
+
+/// Yields the lines of `reader`, without their `\n` or `\r\n`, then returns the first
+/// error or `Ok(())` at the end of the input. Lines that aren't UTF-8 end the flow with
+/// an [`io::ErrorKind::InvalidData`] error.
+///
+/// Resuming with `Some(line)` reads the next line into `line`'s buffer, so a consumer
+/// can hand back lines it's done with; resuming with `None` allocates a new one.
+pub fn read_lines<R: AsyncBufRead>(reader: R) -> ReadLines<R> {
+    ReadLines { reader, line: None }
+}
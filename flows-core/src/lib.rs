@@ -4,10 +4,14 @@ extern crate either;
 extern crate futures_util;
 extern crate pin_project_lite;
 
+pub mod channel;
 pub mod convert;
 pub mod custom_fn;
+pub mod io;
 pub mod my_try;
+pub mod one_of;
 pub mod ops;
+pub mod select;
 mod utils;
 
 use std::{
@@ -24,7 +28,7 @@ use std::{
 };
 
 use async_fn::AsyncFnOnce2;
-use convert::IntoFlow;
+use convert::{FromFlow, IntoFlow};
 use ops::FlowOp;
 use self_ref::{SelfRef, WithLifetime};
 
@@ -68,6 +72,44 @@ pub trait Flow<Resume = ()> {
     {
         op.execute(self)
     }
+
+    /// Collects the items into `C`, as with `C::from_flow(self)`.
+    ///
+    /// `C` can't be inferred from the awaited output, so name it: `collect::<Vec<_>, _>()`.
+    fn collect<C, M>(self) -> C::FromFlowFuture<Self>
+    where
+        Self: Sized,
+        C: FromFlow<Self::Yield, Resume, M>,
+    {
+        C::from_flow(self)
+    }
+
+    /// Fallible counterpart of [`Flow::collect`]; see [`TryFromFlow`](convert::TryFromFlow).
+    fn try_collect<C, M>(self) -> C::TryFromFlowFuture<Self>
+    where
+        Self: Sized,
+        C: convert::TryFromFlow<Self::Yield, Resume, M>,
+    {
+        C::try_from_flow(self)
+    }
+
+    /// Like [`Flow::collect`], but also resolves to the source's `Return`, if `C` let it
+    /// run to completion.
+    fn collect_with_return<C, M>(self) -> impl Future<Output = (C, Option<Self::Return>)>
+    where
+        Self: Sized,
+        C: FromFlow<Self::Yield, Resume, M>,
+    {
+        async move {
+            let mut ret = None;
+            let collected = C::from_flow(convert::StoreReturn {
+                flow: self,
+                ret: &mut ret,
+            })
+            .await;
+            (collected, ret)
+        }
+    }
 }
 
 impl<F, Resume, P> Flow<Resume> for Pin<P>
@@ -256,9 +298,7 @@ impl<Yield, Resume> RawStateRef<Yield, Resume> {
 
 impl<Yield, Resume> Clone for RawStateRef<Yield, Resume> {
     fn clone(&self) -> Self {
-        Self {
-            ptr: self.ptr.clone(),
-        }
+        Self { ptr: self.ptr }
     }
 }
 
@@ -274,14 +314,14 @@ pub struct RawSender<Yield, Resume> {
 }
 
 impl<'state, Yield, Resume> Sender<'state, Yield, Resume> {
-    pub fn next(&mut self, value: Yield) -> Next<Yield, Resume> {
+    pub fn next(&mut self, value: Yield) -> Next<'_, Yield, Resume> {
         Next {
             state_ref: self.state_ref.reborrow_mut(),
             value: Some(value),
         }
     }
 
-    pub fn next_await<Fut>(&mut self, fut: Fut) -> NextAwait<Yield, Resume, Fut>
+    pub fn next_await<Fut>(&mut self, fut: Fut) -> NextAwait<'_, Yield, Resume, Fut>
     where
         Fut: Future<Output = Yield>,
     {
@@ -295,7 +335,7 @@ impl<'state, Yield, Resume> Sender<'state, Yield, Resume> {
         &mut self,
         src: impl IntoFlow<Resume, M, IntoFlow = F>,
         init: Resume,
-    ) -> NextFrom<Yield, Resume, F> {
+    ) -> NextFrom<'_, Yield, Resume, F> {
         NextFrom {
             state_ref: self.state_ref.reborrow_mut(),
             input: Some(init),
@@ -311,6 +351,9 @@ impl<'state, Yield, Resume> Sender<'state, Yield, Resume> {
 }
 
 impl<Yield, Resume> RawSender<Yield, Resume> {
+    /// # Safety
+    ///
+    /// The flow state this sender was created from must outlive `'state`.
     pub unsafe fn into_sender<'state>(self) -> Sender<'state, Yield, Resume> {
         Sender {
             state_ref: self.raw_state_ref.into_state_ref(),
@@ -318,6 +361,10 @@ impl<Yield, Resume> RawSender<Yield, Resume> {
     }
 
     /// Like into_sender, but a little safer as it's bounded by the lifetime of `self`.
+    ///
+    /// # Safety
+    ///
+    /// The flow state this sender was created from must outlive `self`.
     pub unsafe fn as_sender<'this>(&'this mut self) -> Sender<'this, Yield, Resume> {
         self.clone().into_sender()
     }
@@ -505,6 +552,9 @@ pin_project_lite::pin_project!(
             #[pin]
             fut: <Fun as FlowFnLt<'state, Yield, Resume>>::FlowFut,
         },
+        Done {
+            ret: Option<Fun::FlowReturn>,
+        },
     }
 );
 
@@ -543,6 +593,11 @@ where
                 return Poll::Ready(Continue(yielded));
             }
 
+            if let FlowFromFnInnerProj::Done { ret } = inner.as_mut().project() {
+                *input = None;
+                return Poll::Ready(Break(ret.take().unwrap()));
+            }
+
             if let FlowFromFnInnerProj::Fun { fun } = inner.as_mut().project() {
                 let Some(resumed) = input.take() else {
                     return Poll::Pending;
@@ -562,7 +617,7 @@ where
                 state_ref.set(FlowState::Resume(resumed));
             }
 
-            let FlowFromFnInnerProj::Fut { fut } = inner.project() else {
+            let FlowFromFnInnerProj::Fut { fut } = inner.as_mut().project() else {
                 unreachable!()
             };
 
@@ -570,7 +625,15 @@ where
             *input = state_ref.take_resumed();
 
             match poll {
-                Poll::Ready(ret) => Poll::Ready(Break(ret)),
+                // An early resume can let the body run past its last yield, so hand that
+                // out before returning.
+                Poll::Ready(ret) => match state_ref.take_yielded() {
+                    Some(yielded) => {
+                        inner.set(FlowFromFnInner::Done { ret: Some(ret) });
+                        Poll::Ready(Continue(yielded))
+                    }
+                    None => Poll::Ready(Break(ret)),
+                },
                 Poll::Pending => state_ref
                     .take_yielded()
                     .map(Continue)
@@ -598,3 +661,22 @@ where
         }),
     }
 }
+
+/// Like [`flow_from_fn`], but takes a std `async` closure, so no macro is needed to
+/// build a flow: `flow_from_async_fn(async |resume, mut sender| { ... })`.
+pub fn flow_from_async_fn<'x, Yield, Resume, Return>(
+    fun: impl for<'state> AsyncFnOnce(Resume, Sender<'state, Yield, Resume>) -> Return + 'x,
+) -> impl Flow<Resume, Yield = Yield, Return = Return> + 'x
+where
+    Yield: 'x,
+    Resume: 'x,
+{
+    flow_from_fn(move |resume: Resume, sender: Sender<'_, Yield, Resume>| {
+        // Going through the raw sender keeps `'state` out of the future's type, as `flow!` does.
+        let raw_sender = sender.into_raw();
+        async move {
+            let mut raw_sender = raw_sender;
+            fun(resume, unsafe { raw_sender.as_sender() }).await
+        }
+    })
+}
@@ -0,0 +1,54 @@
+use std::{
+    ops::ControlFlow,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use super::Flow;
+
+macro_rules! one_of {
+    ($(#[$meta:meta])* $name:ident, $proj:ident, $($V:ident),+) => {
+        pin_project_lite::pin_project!(
+            $(#[$meta])*
+            #[project = $proj]
+            #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+            pub enum $name<$($V),+> {
+                $($V { #[pin] flow: $V },)+
+            }
+        );
+
+        impl<Res, Y, Ret, $($V),+> Flow<Res> for $name<$($V),+>
+        where
+            $($V: Flow<Res, Yield = Y, Return = Ret>,)+
+        {
+            type Yield = Y;
+            type Return = Ret;
+
+            fn poll_resume(
+                self: Pin<&mut Self>,
+                cx: &mut Context,
+                input: &mut Option<Res>,
+            ) -> Poll<ControlFlow<Self::Return, Self::Yield>> {
+                match self.project() {
+                    $($proj::$V { flow } => flow.poll_resume(cx, input),)+
+                }
+            }
+
+            fn can_resume(self: Pin<&mut Self>) -> bool {
+                match self.project() {
+                    $($proj::$V { flow } => flow.can_resume(),)+
+                }
+            }
+        }
+    };
+}
+
+one_of!(
+    /// One of three flows with the same `Yield` and `Return`; see `one_of!`.
+    OneOf3, OneOf3Proj, A, B, C
+);
+one_of!(OneOf4, OneOf4Proj, A, B, C, D);
+one_of!(OneOf5, OneOf5Proj, A, B, C, D, E);
+one_of!(OneOf6, OneOf6Proj, A, B, C, D, E, F);
+one_of!(OneOf7, OneOf7Proj, A, B, C, D, E, F, G);
+one_of!(OneOf8, OneOf8Proj, A, B, C, D, E, F, G, H);
@@ -0,0 +1,233 @@
+use std::{
+    ops::ControlFlow::{self, Break, Continue},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::{convert::IntoFlow, Flow};
+
+pin_project_lite::pin_project!(
+    /// Combines a tuple of flows, yielding the latest value of each whenever any of them
+    /// yields, once they've all yielded at least once.
+    ///
+    /// The flows are resumed with `()`: an item combines values from all of them, so there's
+    /// no single flow to hand the downstream input to. Values produced in between demands are
+    /// conflated.
+    pub struct CombineLatest<Fs, Rs, Ys, Rets> {
+        #[pin]
+        flows: Fs,
+        res: Rs,
+        latest: Ys,
+        ret: Rets,
+        dirty: bool,
+    }
+);
+
+impl<Fs, Rs: Default, Ys: Default, Rets: Default> CombineLatest<Fs, Rs, Ys, Rets> {
+    pub fn new(flows: Fs) -> Self {
+        Self {
+            flows,
+            res: Default::default(),
+            latest: Default::default(),
+            ret: Default::default(),
+            dirty: false,
+        }
+    }
+}
+
+macro_rules! impl_combine_latest {
+    ($(($F:ident, $R:ident, $Y:ident, $T:ident, $i:tt)),+) => {
+        impl<$($F, $Y, $T),+> Flow
+            for CombineLatest<
+                ($($F,)+),
+                ($(per_flow!($F, Option<()>),)+),
+                ($(Option<$Y>,)+),
+                ($(Option<$T>,)+),
+            >
+        where
+            $(
+                $F: Flow<Yield = $Y, Return = $T>,
+                $Y: Clone,
+            )+
+        {
+            type Yield = ($($Y,)+);
+            type Return = ($($T,)+);
+
+            fn poll_resume(
+                self: Pin<&mut Self>,
+                cx: &mut Context,
+                input: &mut Option<()>,
+            ) -> Poll<ControlFlow<Self::Return, Self::Yield>> {
+                let this = self.project();
+                // SAFETY: the flows are never moved out of the tuple, so pinning it pins them.
+                let flows = unsafe { this.flows.get_unchecked_mut() };
+
+                loop {
+                    let mut progress = false;
+
+                    $(
+                        if this.ret.$i.is_none() {
+                            let mut flow = unsafe { Pin::new_unchecked(&mut flows.$i) };
+                            if this.res.$i.is_none() && flow.as_mut().can_resume() {
+                                this.res.$i = Some(());
+                            }
+
+                            match flow.poll_resume(cx, &mut this.res.$i) {
+                                Poll::Ready(Continue(yielded)) => {
+                                    this.latest.$i = Some(yielded);
+                                    *this.dirty = true;
+                                    progress = true;
+                                }
+                                Poll::Ready(Break(ret)) => {
+                                    this.ret.$i = Some(ret);
+                                    progress = true;
+                                }
+                                Poll::Pending => {}
+                            }
+                        }
+                    )+
+
+                    let ready = *this.dirty && $(this.latest.$i.is_some())&&+;
+
+                    if ready && input.is_some() {
+                        *this.dirty = false;
+                        *input = None;
+                        return Poll::Ready(Continue(($(this.latest.$i.clone().unwrap(),)+)));
+                    }
+
+                    if !ready && $(this.ret.$i.is_some())&&+ {
+                        return Poll::Ready(Break(($(this.ret.$i.take().unwrap(),)+)));
+                    }
+
+                    // Without demand, a single pass is enough to pick up the latest values.
+                    if !progress || input.is_none() {
+                        return Poll::Pending;
+                    }
+                }
+            }
+        }
+    };
+}
+
+for_tuples!(impl_combine_latest);
+
+/// Combines two flows; see [`CombineLatest`]. Use `combine_latest!` for more.
+#[allow(clippy::type_complexity)]
+pub fn combine_latest<Fl0, Fl1, M0, M1>(
+    f0: impl IntoFlow<(), M0, IntoFlow = Fl0>,
+    f1: impl IntoFlow<(), M1, IntoFlow = Fl1>,
+) -> CombineLatest<
+    (Fl0, Fl1),
+    (Option<()>, Option<()>),
+    (Option<Fl0::Yield>, Option<Fl1::Yield>),
+    (Option<Fl0::Return>, Option<Fl1::Return>),
+>
+where
+    Fl0: Flow,
+    Fl1: Flow,
+{
+    CombineLatest::new((f0.into_flow(), f1.into_flow()))
+}
+
+/// Combines a collection of flows of the same type, yielding a `Vec` of their latest values.
+///
+/// Like [`CombineLatest`], the flows are resumed with `()`.
+pub struct CombineLatestAll<Fl: Flow> {
+    flows: Vec<Pin<Box<Fl>>>,
+    res: Vec<Option<()>>,
+    latest: Vec<Option<Fl::Yield>>,
+    ret: Vec<Option<Fl::Return>>,
+    dirty: bool,
+}
+
+impl<Fl: Flow> Unpin for CombineLatestAll<Fl> {}
+
+impl<Fl> Flow for CombineLatestAll<Fl>
+where
+    Fl: Flow,
+    Fl::Yield: Clone,
+{
+    type Yield = Vec<Fl::Yield>;
+    type Return = Vec<Fl::Return>;
+
+    fn poll_resume(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        input: &mut Option<()>,
+    ) -> Poll<ControlFlow<Self::Return, Self::Yield>> {
+        let this = self.get_mut();
+
+        loop {
+            let mut progress = false;
+
+            for (((flow, res), latest), ret) in this
+                .flows
+                .iter_mut()
+                .zip(&mut this.res)
+                .zip(&mut this.latest)
+                .zip(&mut this.ret)
+            {
+                if ret.is_some() {
+                    continue;
+                }
+
+                if res.is_none() && flow.as_mut().can_resume() {
+                    *res = Some(());
+                }
+
+                match flow.as_mut().poll_resume(cx, res) {
+                    Poll::Ready(Continue(yielded)) => {
+                        *latest = Some(yielded);
+                        this.dirty = true;
+                        progress = true;
+                    }
+                    Poll::Ready(Break(returned)) => {
+                        *ret = Some(returned);
+                        progress = true;
+                    }
+                    Poll::Pending => {}
+                }
+            }
+
+            let ready = this.dirty && this.latest.iter().all(Option::is_some);
+
+            if ready && input.is_some() {
+                this.dirty = false;
+                *input = None;
+                return Poll::Ready(Continue(
+                    this.latest.iter().map(|y| y.clone().unwrap()).collect(),
+                ));
+            }
+
+            if !ready && this.ret.iter().all(Option::is_some) {
+                return Poll::Ready(Break(
+                    this.ret.iter_mut().map(|r| r.take().unwrap()).collect(),
+                ));
+            }
+
+            if !progress || input.is_none() {
+                return Poll::Pending;
+            }
+        }
+    }
+}
+
+pub fn combine_latest_all<Fl, M>(
+    flows: impl IntoIterator<Item = impl IntoFlow<(), M, IntoFlow = Fl>>,
+) -> CombineLatestAll<Fl>
+where
+    Fl: Flow,
+{
+    let flows: Vec<_> = flows
+        .into_iter()
+        .map(|flow| Box::pin(flow.into_flow()))
+        .collect();
+
+    CombineLatestAll {
+        res: flows.iter().map(|_| None).collect(),
+        latest: flows.iter().map(|_| None).collect(),
+        ret: flows.iter().map(|_| None).collect(),
+        flows,
+        dirty: false,
+    }
+}
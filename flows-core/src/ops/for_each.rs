@@ -1,20 +1,23 @@
 use std::{
     convert::Infallible,
-    future::Future,
+    future::{self, Future},
     marker::PhantomData,
-    ops::ControlFlow::{self, Break, Continue},
-    pin::Pin,
+    ops::ControlFlow::{Break, Continue},
+    pin::{self, Pin},
     task::{self, Context, Poll},
 };
 
 use crate::{
+    convert::IntoFlow,
     custom_fn::{MapFn, MapFnOnce},
     my_try::MyTry,
     utils::{map_future, MapFuture},
-    Flow, convert::IntoFlow,
+    Flow,
 };
 
-use super::{map_sync, FlowOp, MapSync, WrapOp, MapReturnSync, Compose, compose_with, map_return_sync};
+use super::{
+    compose_with, map_return_sync, map_sync, Compose, FlowOp, MapReturnSync, MapSync, WrapOp,
+};
 
 pin_project_lite::pin_project!(
     pub struct TryFeedback<Res, Fl> {
@@ -117,6 +120,46 @@ impl<T> MapFn<Result<T, Infallible>> for UnwrapInfallible {
     }
 }
 
+pub struct FlattenTry;
+
+impl<T> MapFnOnce<T> for FlattenTry
+where
+    T: MyTry,
+    T::Continue: MyTry<Break = T::Break>,
+{
+    type Out = T::Continue;
+
+    fn map_exec_once(mut self, value: T) -> Self::Out {
+        self.map_exec(value)
+    }
+}
+
+impl<T> MapFn<T> for FlattenTry
+where
+    T: MyTry,
+    T::Continue: MyTry<Break = T::Break>,
+{
+    fn map_exec(&mut self, value: T) -> Self::Out {
+        value.flatten()
+    }
+}
+
+/// Flattens the result of a `try_for_each` op whose source itself returns a [`MyTry`] with the
+/// same break type, so a failure returned by the source ends up alongside the collector's.
+pub fn flatten_try<'f, Res, Fl, Op>(
+    op: Op,
+) -> WrapOp<impl FlowOp<Fl, Res, Output = MapFuture<Op::Output, FlattenTry>> + 'f>
+where
+    Fl: Flow<Res>,
+    Op: 'f + FlowOp<Fl, Res>,
+    Op::Output: Future,
+    <Op::Output as Future>::Output: MyTry,
+    <<Op::Output as Future>::Output as MyTry>::Continue:
+        MyTry<Break = <<Op::Output as Future>::Output as MyTry>::Break>,
+{
+    WrapOp(move |src: Fl| map_future(op.execute(src), FlattenTry))
+}
+
 pub type Feedback<Res, Fl> =
     MapFuture<TryFeedback<Res, MapSync<Fl, WrapOk<Infallible>>>, UnwrapInfallible>;
 
@@ -211,91 +254,25 @@ where
     for_each_init(src, collector, Default::default())
 }
 
-pin_project_lite::pin_project!(
-    struct MergeAll<Fl1, Fl2, Ret, M> {
-        #[pin]
-        main_flow: Fl1,
-        sub_flows: Vec<Pin<Box<Fl2>>>,
-        ret: Option<Ret>,
-        _m: PhantomData<M>,
-    }
-);
-
-impl<Fl1, Fl2, Res, Ret, M> Flow<Res> for MergeAll<Fl1, Fl2, Ret, M>
+/// Drives the source to completion, resuming it with the output of `fun` for each item.
+///
+/// This is the macro-free counterpart of `for_each!`, taking a std `async` closure.
+pub fn for_each_async<'f, Res, Fl, Fun>(
+    mut fun: Fun,
+) -> WrapOp<impl FlowOp<Fl, Res, Output = impl Future<Output = Fl::Return> + 'f> + 'f>
 where
-    Fl1: Flow<Res, Return = Ret>,
-    Fl1::Yield: IntoFlow<Res, M, IntoFlow = Fl2>,
-    Fl2: Flow<Res>,
+    Fl: 'f + Flow<Res>,
+    Fun: 'f + AsyncFnMut(Fl::Yield) -> Res,
+    Res: 'f + Default,
 {
-    type Yield = Fl2::Yield;
-    type Return = Ret;
-
-    fn poll_resume(
-        mut self: Pin<&mut Self>,
-        cx: &mut Context,
-        input: &mut Option<Res>,
-    ) -> Poll<ControlFlow<Self::Return, Self::Yield>> {
-        let mut no_input = None;
-        let mut this = self.as_mut().project();
-
-        if this.ret.is_none() {
-            let can_resume = input.is_some() && this.main_flow.as_mut().can_resume();
-
-            match task::ready!(this
-                .main_flow
-                .poll_resume(cx, if can_resume { input } else { &mut no_input }))
-            {
-                Continue(sub_flow) => {
-                    this.sub_flows.push(Box::pin(sub_flow.into_flow()));
-                }
-                Break(returned) => return Poll::Ready(Break(returned)),
+    WrapOp(move |src: Fl| async move {
+        let mut src = pin::pin!(src);
+        let mut input = Some(Res::default());
+        loop {
+            match future::poll_fn(|cx| src.as_mut().poll_resume(cx, &mut input)).await {
+                Continue(item) => input = Some(fun(item).await),
+                Break(ret) => return ret,
             }
         }
-
-        {
-            let mut i = 0;
-            while i < this.sub_flows.len() {
-                let sub_flow = &mut this.sub_flows[i];
-                let can_resume = input.is_some() && sub_flow.as_mut().can_resume();
-
-                match sub_flow
-                    .as_mut()
-                    .poll_resume(cx, if can_resume { input } else { &mut no_input })
-                {
-                    Poll::Ready(Continue(item)) => return Poll::Ready(Continue(item)),
-                    Poll::Ready(Break(_)) => {
-                        this.sub_flows.swap_remove(i);
-                    }
-                    Poll::Pending => {
-                        i += 1;
-                    }
-                }
-            }
-        }
-
-        this.sub_flows
-            .is_empty()
-            .then(|| this.ret.take())
-            .flatten()
-            .map(Break)
-            .map(Poll::Ready)
-            .unwrap_or(Poll::Pending)
-    }
-}
-
-pub fn merge_all<'f, Y, Res, Ret, M, Fl1, Fl2>(
-) -> WrapOp<impl FlowOp<Fl1, Res, Output = impl Flow<Res, Yield = Y, Return = Ret> + 'f> + 'f>
-where
-    Fl1: 'f + Flow<Res, Return = Ret>,
-    Fl1::Yield: IntoFlow<Res, M, Yield = Y, IntoFlow = Fl2>,
-    Fl2: 'f + Flow<Res, Yield = Y>,
-    Ret: 'f,
-    M: 'f,
-{
-    WrapOp(|main_flow: Fl1| MergeAll::<Fl1, Fl2, Ret, M> {
-        main_flow,
-        sub_flows: Vec::new(),
-        ret: None,
-        _m: PhantomData,
     })
 }
@@ -1,10 +1,22 @@
 use std::{
+    collections::VecDeque,
+    future::{self, Future},
     ops::ControlFlow::{self, Break, Continue},
-    pin::Pin,
+    pin::{self, Pin},
     task::{Context, Poll},
 };
 
-use crate::{custom_fn::{MapFn, MapFnOnce}, my_try::MyTry, Flow};
+use futures_util::{
+    stream::{FuturesOrdered, FuturesUnordered},
+    Stream, StreamExt,
+};
+
+use crate::{
+    custom_fn::{MapFn, MapFnOnce},
+    flow_from_async_fn,
+    my_try::MyTry,
+    Flow, Sender,
+};
 
 use super::{FlowOp, WrapOp};
 
@@ -94,104 +106,196 @@ where
     })
 }
 
+/// The set of in-flight futures of a [`MapConcurrent`].
+pub trait ConcurrentQueue<Fut: Future>: Stream<Item = Fut::Output> + Unpin {
+    fn push(&mut self, fut: Fut);
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<Fut: Future> ConcurrentQueue<Fut> for FuturesOrdered<Fut> {
+    fn push(&mut self, fut: Fut) {
+        self.push_back(fut);
+    }
+    fn len(&self) -> usize {
+        FuturesOrdered::len(self)
+    }
+}
+
+impl<Fut: Future> ConcurrentQueue<Fut> for FuturesUnordered<Fut> {
+    fn push(&mut self, fut: Fut) {
+        FuturesUnordered::push(self, fut);
+    }
+    fn len(&self) -> usize {
+        FuturesUnordered::len(self)
+    }
+}
+
 pin_project_lite::pin_project!(
-    pub struct Zip<F0, F1, R0, R1, Y0, Y1> {
-        #[pin]
-        f0: F0,
+    pub struct MapConcurrent<Fl, Fun, Q, Out, Ret> {
         #[pin]
-        f1: F1,
-        res: (Option<R0>, Option<R1>),
-        yld: (Option<Y0>, Option<Y1>),
+        flow: Fl,
+        fun: Fun,
+        queue: Q,
+        // outputs of completed futures, waiting for the downstream
+        done: VecDeque<Out>,
+        limit: usize,
+        input: Option<()>,
+        ret: Option<Ret>,
     }
 );
 
-impl<R0, R1, Y0, Y1, Ret, F0, F1> Flow<(R0, R1)> for Zip<F0, F1, R0, R1, Y0, Y1>
+impl<Fl, Fun, Fut, Q, Ret> Flow for MapConcurrent<Fl, Fun, Q, Fut::Output, Ret>
 where
-    F0: Flow<R0, Yield = Y0, Return = Ret>,
-    F1: Flow<R1, Yield = Y1, Return = Ret>,
+    Fl: Flow<Return = Ret>,
+    Fun: MapFn<Fl::Yield, Out = Fut>,
+    Fut: Future,
+    Q: ConcurrentQueue<Fut>,
 {
-    type Yield = (Y0, Y1);
+    type Yield = Fut::Output;
     type Return = Ret;
 
     fn poll_resume(
         self: Pin<&mut Self>,
         cx: &mut Context,
-        input: &mut Option<(R0, R1)>,
+        input: &mut Option<()>,
     ) -> Poll<ControlFlow<Self::Return, Self::Yield>> {
         let mut this = self.project();
 
-        let mut inputs = match input.take() {
-            Some((v1, v2)) => {
-                *this.res = Default::default();
-                (Some(v1), Some(v2))
+        while this.ret.is_none() && this.queue.len() + this.done.len() < *this.limit {
+            if this.input.is_none() && this.flow.as_mut().can_resume() {
+                *this.input = Some(());
             }
-            None => (None, None),
-        };
-
-        fn inner<Res, F>(
-            f: Pin<&mut F>,
-            input: &mut Option<Res>,
-            res: &mut Option<Res>,
-            yld: &mut Option<F::Yield>,
-            cx: &mut Context,
-        ) -> Poll<ControlFlow<F::Return>>
-        where
-            F: Flow<Res>,
-        {
-            f.poll_resume(cx, if input.is_some() { input } else { res })
-                .map(|ctrl| {
-                    MyTry::map(ctrl, |value| {
-                        *yld = Some(value);
-                    })
-                })
-        }
 
-        let polls = (
-            inner(
-                this.f0.as_mut(),
-                &mut inputs.0,
-                &mut this.res.0,
-                &mut this.yld.0,
-                cx,
-            ),
-            inner(
-                this.f1.as_mut(),
-                &mut inputs.1,
-                &mut this.res.1,
-                &mut this.yld.1,
-                cx,
-            ),
-        );
-
-        match inputs {
-            (Some(r0), Some(r1)) => {
-                *input = Some((r0, r1));
-            }
-            inputs => {
-                *this.res = inputs;
+            match this.flow.as_mut().poll_resume(cx, this.input) {
+                Poll::Ready(Continue(item)) => this.queue.push(this.fun.map_exec(item)),
+                Poll::Ready(Break(ret)) => *this.ret = Some(ret),
+                Poll::Pending => break,
             }
         }
 
-        match polls {
-            (Poll::Ready(Break(ret)), _) | (_, Poll::Ready(Break(ret))) => {
-                return Poll::Ready(Break(ret))
-            }
-            (Poll::Pending, Poll::Pending) => return Poll::Pending,
-            _ => {
-                if let (Some(_), Some(_)) = this.yld {
-                    Poll::Ready(Continue((
-                        this.yld.0.take().unwrap(),
-                        this.yld.1.take().unwrap(),
-                    )))
-                } else {
-                    Poll::Pending
-                }
+        // The futures keep running while the downstream isn't asking for their outputs.
+        while let Poll::Ready(Some(out)) = this.queue.poll_next_unpin(cx) {
+            this.done.push_back(out);
+        }
+
+        if input.is_none() {
+            return Poll::Pending;
+        }
+
+        if let Some(out) = this.done.pop_front() {
+            *input = None;
+            return Poll::Ready(Continue(out));
+        }
+
+        if this.queue.is_empty() {
+            if let Some(ret) = this.ret.take() {
+                *input = None;
+                return Poll::Ready(Break(ret));
             }
         }
-    }
 
-    fn can_resume(self: Pin<&mut Self>) -> bool {
-        let this = self.project();
-        this.f0.can_resume() || this.f1.can_resume()
+        Poll::Pending
     }
 }
+
+#[allow(clippy::type_complexity)]
+fn map_concurrent_with<'f, Fl, Fun, Fut, Q>(
+    limit: usize,
+    fun: Fun,
+    queue: Q,
+) -> WrapOp<impl FlowOp<Fl, (), Output = MapConcurrent<Fl, Fun, Q, Fut::Output, Fl::Return>> + 'f>
+where
+    Fl: 'f + Flow,
+    Fun: 'f + MapFn<Fl::Yield, Out = Fut>,
+    Fut: 'f + Future,
+    Q: 'f + ConcurrentQueue<Fut>,
+{
+    assert!(limit > 0, "map_concurrent requires a limit of at least 1");
+
+    WrapOp(move |flow: Fl| MapConcurrent {
+        flow,
+        fun,
+        queue,
+        done: VecDeque::new(),
+        limit,
+        input: None,
+        ret: None,
+    })
+}
+
+/// Maps each item to a future, keeping up to `limit` of them in flight and
+/// yielding their outputs in source order.
+///
+/// Outputs waiting for the downstream count toward `limit`. The source is resumed ahead of
+/// the downstream, so it only takes `()`.
+///
+/// # Panics
+///
+/// Panics if `limit` is zero.
+#[allow(clippy::type_complexity)]
+pub fn map_concurrent<'f, Fl, Fun, Fut>(
+    limit: usize,
+    fun: Fun,
+) -> WrapOp<
+    impl FlowOp<
+            Fl,
+            (),
+            Output = MapConcurrent<Fl, Fun, FuturesOrdered<Fut>, Fut::Output, Fl::Return>,
+        > + 'f,
+>
+where
+    Fl: 'f + Flow,
+    Fun: 'f + MapFn<Fl::Yield, Out = Fut>,
+    Fut: 'f + Future,
+{
+    map_concurrent_with(limit, fun, FuturesOrdered::new())
+}
+
+/// Like [`map_concurrent`], but yields outputs as soon as their futures complete.
+#[allow(clippy::type_complexity)]
+pub fn map_concurrent_unordered<'f, Fl, Fun, Fut>(
+    limit: usize,
+    fun: Fun,
+) -> WrapOp<
+    impl FlowOp<
+            Fl,
+            (),
+            Output = MapConcurrent<Fl, Fun, FuturesUnordered<Fut>, Fut::Output, Fl::Return>,
+        > + 'f,
+>
+where
+    Fl: 'f + Flow,
+    Fun: 'f + MapFn<Fl::Yield, Out = Fut>,
+    Fut: 'f + Future,
+{
+    map_concurrent_with(limit, fun, FuturesUnordered::new())
+}
+
+/// Maps each item through `fun`, passing resume values through to the source.
+///
+/// This is the macro-free counterpart of `map_each!`, taking a std `async` closure.
+pub fn map_each_async<'f, Y, Res, Fl, Fun>(
+    mut fun: Fun,
+) -> WrapOp<impl FlowOp<Fl, Res, Output = impl Flow<Res, Yield = Y, Return = Fl::Return> + 'f> + 'f>
+where
+    Fl: 'f + Flow<Res>,
+    Fun: 'f + AsyncFnMut(Fl::Yield) -> Y,
+    Y: 'f,
+    Res: 'f,
+{
+    WrapOp(move |src: Fl| {
+        flow_from_async_fn(async move |resume: Res, mut sender: Sender<'_, Y, Res>| {
+            let mut src = pin::pin!(src);
+            let mut input = Some(resume);
+            loop {
+                match future::poll_fn(|cx| src.as_mut().poll_resume(cx, &mut input)).await {
+                    Continue(item) => input = Some(sender.next(fun(item).await).await),
+                    Break(ret) => return ret,
+                }
+            }
+        })
+    })
+}
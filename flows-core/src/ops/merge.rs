@@ -0,0 +1,422 @@
+use std::{
+    collections::VecDeque,
+    marker::PhantomData,
+    mem,
+    ops::ControlFlow::{self, Break, Continue},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+    task::{Context, Poll, Waker},
+};
+
+use futures_util::task::{waker, ArcWake, AtomicWaker};
+use slab::Slab;
+
+use crate::{convert::IntoFlow, Flow};
+
+use super::{FlowOp, WrapOp, MAIN_FLOW_BUDGET};
+
+/// Indices of sub-flows that have been woken, shared with their wakers.
+#[derive(Default)]
+struct ReadyQueue {
+    indices: Mutex<Vec<usize>>,
+    waker: AtomicWaker,
+}
+
+impl ReadyQueue {
+    fn lock(&self) -> MutexGuard<'_, Vec<usize>> {
+        self.indices.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+struct SubWaker {
+    index: usize,
+    queued: AtomicBool,
+    queue: Arc<ReadyQueue>,
+}
+
+impl SubWaker {
+    fn schedule(&self) {
+        if !self.queued.swap(true, Ordering::AcqRel) {
+            self.queue.lock().push(self.index);
+            self.queue.waker.wake();
+        }
+    }
+}
+
+impl ArcWake for SubWaker {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.schedule();
+    }
+}
+
+struct SubFlow<Fl, Res, Y> {
+    flow: Pin<Box<Fl>>,
+    input: Option<Res>,
+    item: Option<Y>,
+    handle: Arc<SubWaker>,
+    waker: Waker,
+}
+
+pin_project_lite::pin_project!(
+    struct MergeAll<Fl1, Fl2, Res, Y, Ret, M> {
+        #[pin]
+        main_flow: Fl1,
+        main_input: Option<Res>,
+        sub_flows: Slab<SubFlow<Fl2, Res, Y>>,
+        ready: Arc<ReadyQueue>,
+        yielded: VecDeque<usize>,
+        // the sub-flow whose item was yielded last, waiting for the downstream's answer
+        answering: Option<usize>,
+        demand: bool,
+        limit: usize,
+        ret: Option<Ret>,
+        _m: PhantomData<M>,
+    }
+);
+
+impl<Fl1, Fl2, Res, Y, Ret, M> Flow<Res> for MergeAll<Fl1, Fl2, Res, Y, Ret, M>
+where
+    Fl1: Flow<Res, Return = Ret>,
+    Fl1::Yield: IntoFlow<Res, M, IntoFlow = Fl2>,
+    Fl2: Flow<Res, Yield = Y>,
+    Res: Default,
+{
+    type Yield = Y;
+    type Return = Ret;
+
+    fn poll_resume(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        input: &mut Option<Res>,
+    ) -> Poll<ControlFlow<Self::Return, Self::Yield>> {
+        let this = self.as_mut().project();
+        if input.is_some() && !*this.demand {
+            *this.demand = true;
+            // the downstream input answers the last item, so it resumes the sub-flow that
+            // yielded it
+            if let Some(index) = this.answering.take() {
+                let sub_flow = &mut this.sub_flows[index];
+                sub_flow.input = input.take();
+                sub_flow.handle.schedule();
+            }
+        }
+
+        let mut budget = MAIN_FLOW_BUDGET;
+
+        loop {
+            let mut this = self.as_mut().project();
+            let mut progress = false;
+
+            this.ready.waker.register(cx.waker());
+
+            let pull_main = this.ret.is_none() && this.sub_flows.len() < *this.limit;
+            if pull_main && budget == 0 {
+                // let the sub-flows catch up and come back for the rest of the main flow later
+                cx.waker().wake_by_ref();
+            } else if pull_main {
+                budget -= 1;
+                if this.main_input.is_none() && this.main_flow.as_mut().can_resume() {
+                    *this.main_input = Some(Res::default());
+                }
+
+                match this.main_flow.poll_resume(cx, this.main_input) {
+                    Poll::Ready(Continue(sub_flow)) => {
+                        let entry = this.sub_flows.vacant_entry();
+                        let handle = Arc::new(SubWaker {
+                            index: entry.key(),
+                            queued: AtomicBool::new(false),
+                            queue: this.ready.clone(),
+                        });
+                        handle.schedule();
+                        entry.insert(SubFlow {
+                            flow: Box::pin(sub_flow.into_flow()),
+                            input: None,
+                            item: None,
+                            waker: waker(handle.clone()),
+                            handle,
+                        });
+                        progress = true;
+                    }
+                    Poll::Ready(Break(returned)) => *this.ret = Some(returned),
+                    Poll::Pending => {}
+                }
+            }
+
+            // Only sub-flows woken since the last poll are polled again. A stale index
+            // left behind by a finished sub-flow at worst causes one spurious poll.
+            let ready = mem::take(&mut *this.ready.lock());
+            for index in ready {
+                let Some(sub_flow) = this.sub_flows.get_mut(index) else {
+                    continue;
+                };
+
+                // Cleared even if the sub-flow can't be resumed yet, so the schedule once the
+                // downstream answers its item isn't mistaken for a duplicate.
+                sub_flow.handle.queued.store(false, Ordering::Release);
+
+                if sub_flow.item.is_some() || *this.answering == Some(index) {
+                    continue;
+                }
+
+                if sub_flow.input.is_none() && sub_flow.flow.as_mut().can_resume() {
+                    sub_flow.input = Some(Res::default());
+                }
+
+                let mut sub_cx = Context::from_waker(&sub_flow.waker);
+                match sub_flow
+                    .flow
+                    .as_mut()
+                    .poll_resume(&mut sub_cx, &mut sub_flow.input)
+                {
+                    Poll::Ready(Continue(item)) => {
+                        sub_flow.item = Some(item);
+                        this.yielded.push_back(index);
+                    }
+                    Poll::Ready(Break(_)) => {
+                        this.sub_flows.remove(index);
+                        progress |= this.ret.is_none();
+                    }
+                    Poll::Pending => {}
+                }
+            }
+
+            // Items are handed out in the order they arrived, and a sub-flow is only
+            // resumed once the downstream has answered its item, so every sub-flow gets
+            // its turn.
+            if *this.demand {
+                if let Some(index) = this.yielded.pop_front() {
+                    let item = this.sub_flows[index].item.take().unwrap();
+                    *this.answering = Some(index);
+                    *this.demand = false;
+                    *input = None;
+                    return Poll::Ready(Continue(item));
+                }
+            }
+
+            if this.sub_flows.is_empty() {
+                if let Some(ret) = this.ret.take() {
+                    return Poll::Ready(Break(ret));
+                }
+            }
+
+            if !progress {
+                return Poll::Pending;
+            }
+        }
+    }
+}
+
+/// Runs every flow yielded by the main flow concurrently, yielding their items as they arrive.
+///
+/// The downstream input after an item resumes the sub-flow that yielded it. The main flow, and
+/// each sub-flow's first resume, get `Res::default()`.
+pub fn merge_all<'f, Y, Res, Ret, M, Fl1, Fl2>(
+) -> WrapOp<impl FlowOp<Fl1, Res, Output = impl Flow<Res, Yield = Y, Return = Ret> + 'f> + 'f>
+where
+    Fl1: 'f + Flow<Res, Return = Ret>,
+    Fl1::Yield: IntoFlow<Res, M, Yield = Y, IntoFlow = Fl2>,
+    Fl2: 'f + Flow<Res, Yield = Y>,
+    Res: 'f + Default,
+    Y: 'f,
+    Ret: 'f,
+    M: 'f,
+{
+    merge_all_limited(usize::MAX)
+}
+
+/// Like [`merge_all`], but with at most `limit` sub-flows running at once.
+/// The main flow isn't polled again until a sub-flow returns.
+///
+/// # Panics
+///
+/// Panics if `limit` is zero.
+pub fn merge_all_limited<'f, Y, Res, Ret, M, Fl1, Fl2>(
+    limit: usize,
+) -> WrapOp<impl FlowOp<Fl1, Res, Output = impl Flow<Res, Yield = Y, Return = Ret> + 'f> + 'f>
+where
+    Fl1: 'f + Flow<Res, Return = Ret>,
+    Fl1::Yield: IntoFlow<Res, M, Yield = Y, IntoFlow = Fl2>,
+    Fl2: 'f + Flow<Res, Yield = Y>,
+    Res: 'f + Default,
+    Y: 'f,
+    Ret: 'f,
+    M: 'f,
+{
+    assert!(
+        limit > 0,
+        "merge_all_limited requires a limit of at least 1"
+    );
+
+    WrapOp(move |main_flow: Fl1| MergeAll::<Fl1, Fl2, Res, Y, Ret, M> {
+        main_flow,
+        main_input: None,
+        sub_flows: Slab::new(),
+        ready: Arc::default(),
+        yielded: VecDeque::new(),
+        answering: None,
+        demand: false,
+        limit,
+        ret: None,
+        _m: PhantomData,
+    })
+}
+
+pin_project_lite::pin_project!(
+    /// Merges a tuple of flows with the same `Yield`, yielding items as they arrive.
+    ///
+    /// The downstream input after an item resumes the flow that yielded it, which isn't polled
+    /// again until then; each flow's first resume gets `Res::default()`. Flows with items ready
+    /// take turns, and the merged flow returns a tuple of their returns once they've all
+    /// returned.
+    ///
+    /// `Rs`, `Ys` and `Rets` are tuples of `Option`s holding each flow's pending input, item
+    /// and return.
+    pub struct Merge<Fs, Rs, Ys, Rets> {
+        #[pin]
+        flows: Fs,
+        res: Rs,
+        yld: Ys,
+        ret: Rets,
+        next: usize,
+        // the flow whose item was yielded last, waiting for the downstream's answer
+        answering: Option<usize>,
+        demand: bool,
+    }
+);
+
+impl<Fs, Rs: Default, Ys: Default, Rets: Default> Merge<Fs, Rs, Ys, Rets> {
+    pub fn new(flows: Fs) -> Self {
+        Self {
+            flows,
+            res: Default::default(),
+            yld: Default::default(),
+            ret: Default::default(),
+            next: 0,
+            answering: None,
+            demand: false,
+        }
+    }
+}
+
+macro_rules! impl_merge {
+    ($(($F:ident, $R:ident, $Y:ident, $T:ident, $i:tt)),+) => {
+        impl<Res, Y, $($F, $T),+> Flow<Res>
+            for Merge<
+                ($($F,)+),
+                ($(per_flow!($F, Option<Res>),)+),
+                ($(per_flow!($F, Option<Y>),)+),
+                ($(Option<$T>,)+),
+            >
+        where
+            $($F: Flow<Res, Yield = Y, Return = $T>,)+
+            Res: Default,
+        {
+            type Yield = Y;
+            type Return = ($($T,)+);
+
+            fn poll_resume(
+                self: Pin<&mut Self>,
+                cx: &mut Context,
+                input: &mut Option<Res>,
+            ) -> Poll<ControlFlow<Self::Return, Self::Yield>> {
+                const LEN: usize = [$($i),+].len();
+
+                let this = self.project();
+                // SAFETY: the flows are never moved out of the tuple, so pinning it pins them.
+                let flows = unsafe { this.flows.get_unchecked_mut() };
+
+                if input.is_some() && !*this.demand {
+                    *this.demand = true;
+                    match this.answering.take() {
+                        $(Some($i) => this.res.$i = input.take(),)+
+                        _ => {}
+                    }
+                }
+
+                $(
+                    if this.ret.$i.is_none()
+                        && this.yld.$i.is_none()
+                        && *this.answering != Some($i)
+                    {
+                        let mut flow = unsafe { Pin::new_unchecked(&mut flows.$i) };
+                        if this.res.$i.is_none() && flow.as_mut().can_resume() {
+                            this.res.$i = Some(Res::default());
+                        }
+
+                        match flow.poll_resume(cx, &mut this.res.$i) {
+                            Poll::Ready(Continue(item)) => this.yld.$i = Some(item),
+                            Poll::Ready(Break(ret)) => this.ret.$i = Some(ret),
+                            Poll::Pending => {}
+                        }
+                    }
+                )+
+
+                if *this.demand {
+                    for offset in 0..LEN {
+                        #[allow(clippy::modulo_one)] // a single flow
+                        let index = (*this.next + offset) % LEN;
+                        let item = match index {
+                            $($i => this.yld.$i.take(),)+
+                            _ => unreachable!(),
+                        };
+                        if let Some(item) = item {
+                            *this.next = index + 1;
+                            *this.answering = Some(index);
+                            *this.demand = false;
+                            *input = None;
+                            return Poll::Ready(Continue(item));
+                        }
+                    }
+                }
+
+                if $(this.ret.$i.is_some())&&+ {
+                    Poll::Ready(Break(($(this.ret.$i.take().unwrap(),)+)))
+                } else {
+                    Poll::Pending
+                }
+            }
+        }
+
+        impl<Res, Y, $($F, $T),+> IntoFlow<Res, Merge<(), (), (), ()>> for ($($F,)+)
+        where
+            $($F: Flow<Res, Yield = Y, Return = $T>,)+
+            Res: Default,
+        {
+            type Yield = Y;
+            type Return = ($($T,)+);
+            type IntoFlow = Merge<
+                ($($F,)+),
+                ($(per_flow!($F, Option<Res>),)+),
+                ($(per_flow!($F, Option<Y>),)+),
+                ($(Option<$T>,)+),
+            >;
+
+            fn into_flow(self) -> Self::IntoFlow {
+                Merge::new(self)
+            }
+        }
+    };
+}
+
+for_tuples!(impl_merge);
+
+/// Merges two flows; see [`Merge`]. Use `merge!` for more.
+#[allow(clippy::type_complexity)]
+pub fn merge<Res, Fl0, Fl1, M0, M1>(
+    f0: impl IntoFlow<Res, M0, IntoFlow = Fl0>,
+    f1: impl IntoFlow<Res, M1, IntoFlow = Fl1>,
+) -> Merge<
+    (Fl0, Fl1),
+    (Option<Res>, Option<Res>),
+    (Option<Fl0::Yield>, Option<Fl0::Yield>),
+    (Option<Fl0::Return>, Option<Fl1::Return>),
+>
+where
+    Fl0: Flow<Res>,
+    Fl1: Flow<Res, Yield = Fl0::Yield>,
+{
+    Merge::new((f0.into_flow(), f1.into_flow()))
+}
@@ -1,6 +1,35 @@
+/// Invokes `$m!` once for every tuple arity from 1 to 12, passing one
+/// `(Flow, Resume, Yield, Return, index)` group of type parameters per element.
+macro_rules! for_tuples {
+    ($m:ident) => {
+        for_tuples! { @ $m [] [
+            (F0, R0, Y0, T0, 0) (F1, R1, Y1, T1, 1) (F2, R2, Y2, T2, 2) (F3, R3, Y3, T3, 3)
+            (F4, R4, Y4, T4, 4) (F5, R5, Y5, T5, 5) (F6, R6, Y6, T6, 6) (F7, R7, Y7, T7, 7)
+            (F8, R8, Y8, T8, 8) (F9, R9, Y9, T9, 9) (F10, R10, Y10, T10, 10)
+            (F11, R11, Y11, T11, 11)
+        ] }
+    };
+    (@ $m:ident [$($done:tt)*] [$next:tt $($rest:tt)*]) => {
+        $m! { $($done,)* $next }
+        for_tuples! { @ $m [$($done)* $next] [$($rest)*] }
+    };
+    (@ $m:ident [$($done:tt)*] []) => {};
+}
+
+/// Expands to `$ty` once per flow type `$F`, for tuples whose elements share one type.
+macro_rules! per_flow {
+    ($F:ident, $ty:ty) => {
+        $ty
+    };
+}
+
+mod combine;
 mod compose;
 mod for_each;
 mod map;
+mod merge;
+mod query;
+mod zip;
 
 use std::{
     ops::ControlFlow::{self, Break, Continue},
@@ -9,9 +38,17 @@ use std::{
 };
 
 use crate::{convert::IntoFlow, Flow};
+pub use combine::*;
 pub use compose::*;
 pub use for_each::*;
 pub use map::*;
+pub use merge::*;
+pub use query::*;
+pub use zip::*;
+
+/// How many times a flattening op may resume its main flow in one poll. A main flow that
+/// never returns `Pending`, like `repeat`, would otherwise keep the op from yielding.
+const MAIN_FLOW_BUDGET: usize = 32;
 
 pub trait FlowOp<Fl: Flow<R>, R> {
     type Output;
@@ -220,3 +257,303 @@ where
         ret: None,
     })
 }
+
+pin_project_lite::pin_project!(
+    struct ExhaustAll<Fl1, Fl2, In, Ret> {
+        #[pin]
+        main_flow: Fl1,
+        #[pin]
+        sub_flow: Option<Fl2>,
+        input: Option<In>,
+        ret: Option<Ret>,
+    }
+);
+
+impl<Fl1, Fl2, Yield, In, Res, Ret> Flow<Res> for ExhaustAll<Fl1, Fl2, In, Ret>
+where
+    Fl1: Flow<In, Yield = Fl2, Return = Ret>,
+    Fl2: Flow<Res, Yield = Yield>,
+    In: Default,
+    Res: Default,
+{
+    type Yield = Yield;
+    type Return = Ret;
+
+    fn poll_resume(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        input: &mut Option<Res>,
+    ) -> Poll<ControlFlow<Self::Return, Self::Yield>> {
+        let mut budget = MAIN_FLOW_BUDGET;
+
+        loop {
+            let mut this = self.as_mut().project();
+
+            while this.ret.is_none() {
+                if budget == 0 {
+                    // give the sub-flow its turn and come back for the rest later
+                    cx.waker().wake_by_ref();
+                    break;
+                }
+                budget -= 1;
+
+                if this.input.is_none() && this.main_flow.as_mut().can_resume() {
+                    *this.input = Some(Default::default());
+                }
+
+                match this.main_flow.as_mut().poll_resume(cx, this.input) {
+                    Poll::Ready(Continue(sub_flow)) => {
+                        if this.sub_flow.is_none() {
+                            this.sub_flow.set(Some(sub_flow));
+                        }
+                    }
+                    Poll::Ready(Break(output)) => *this.ret = Some(output),
+                    Poll::Pending => break,
+                }
+            }
+
+            let had_input = input.is_some();
+
+            match this
+                .sub_flow
+                .as_mut()
+                .as_pin_mut()
+                .map(|fl| fl.poll_resume(cx, input))
+            {
+                Some(Poll::Ready(Continue(yielded))) => return Poll::Ready(Continue(yielded)),
+                Some(Poll::Ready(Break(_))) => {
+                    this.sub_flow.set(None);
+                    if had_input && input.is_none() {
+                        *input = Some(Default::default());
+                    }
+                }
+                Some(Poll::Pending) => return Poll::Pending,
+                None => {
+                    return this
+                        .ret
+                        .take()
+                        .map(Break)
+                        .map(Poll::Ready)
+                        .unwrap_or(Poll::Pending)
+                }
+            }
+        }
+    }
+}
+
+/// Flattens the flows yielded by the main flow, dropping any that arrive while
+/// the current one is still running.
+///
+/// The main flow is driven with default inputs so it keeps running in the background.
+pub fn exhaust_all<'f, Y, In, Res, Ret, M, Fl>(
+) -> WrapOp<impl FlowOp<Fl, In, Output = impl Flow<Res, Yield = Y, Return = Ret> + 'f> + 'f>
+where
+    In: 'f + Default,
+    Res: 'f + Default,
+    Ret: 'f,
+    M: 'f,
+    Fl: 'f + Flow<In, Return = Ret>,
+    Fl::Yield: IntoFlow<Res, M, Yield = Y>,
+{
+    WrapOp(|main_flow: Fl| ExhaustAll {
+        main_flow: main_flow.then(map_sync(IntoFlow::into_flow)),
+        sub_flow: None,
+        input: None,
+        ret: None,
+    })
+}
+
+pin_project_lite::pin_project!(
+    struct SwitchAll<Fl1, Fl2, In, Y, Ret> {
+        #[pin]
+        main_flow: Fl1,
+        #[pin]
+        sub_flow: Option<Fl2>,
+        main_input: Option<In>,
+        sub_input: Option<()>,
+        item: Option<Y>,
+        ret: Option<Ret>,
+    }
+);
+
+impl<Fl1, Fl2, Y, In, Ret> Flow for SwitchAll<Fl1, Fl2, In, Y, Ret>
+where
+    Fl1: Flow<In, Yield = Fl2, Return = Ret>,
+    Fl2: Flow<Yield = Y>,
+    In: Default,
+{
+    type Yield = Y;
+    type Return = Ret;
+
+    fn poll_resume(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        input: &mut Option<()>,
+    ) -> Poll<ControlFlow<Self::Return, Self::Yield>> {
+        let mut this = self.project();
+        let mut budget = MAIN_FLOW_BUDGET;
+
+        while this.ret.is_none() {
+            if budget == 0 {
+                // give the latest sub-flow its turn and come back for the rest later
+                cx.waker().wake_by_ref();
+                break;
+            }
+            budget -= 1;
+
+            if this.main_input.is_none() && this.main_flow.as_mut().can_resume() {
+                *this.main_input = Some(Default::default());
+            }
+
+            match this.main_flow.as_mut().poll_resume(cx, this.main_input) {
+                Poll::Ready(Continue(sub_flow)) => {
+                    // Dropping the old sub-flow here cancels it, even mid-await, and drops
+                    // its item if the downstream hasn't taken it yet.
+                    this.sub_flow.set(Some(sub_flow));
+                    *this.sub_input = None;
+                    *this.item = None;
+                }
+                Poll::Ready(Break(output)) => *this.ret = Some(output),
+                Poll::Pending => break,
+            }
+        }
+
+        if this.item.is_none() {
+            if let Some(mut sub_flow) = this.sub_flow.as_mut().as_pin_mut() {
+                if this.sub_input.is_none() && sub_flow.as_mut().can_resume() {
+                    *this.sub_input = Some(());
+                }
+
+                match sub_flow.poll_resume(cx, this.sub_input) {
+                    Poll::Ready(Continue(yielded)) => *this.item = Some(yielded),
+                    Poll::Ready(Break(_)) => this.sub_flow.set(None),
+                    Poll::Pending => {}
+                }
+            }
+        }
+
+        if input.is_some() {
+            if let Some(item) = this.item.take() {
+                *input = None;
+                return Poll::Ready(Continue(item));
+            }
+        }
+
+        if this.sub_flow.is_none() {
+            if let Some(ret) = this.ret.take() {
+                return Poll::Ready(Break(ret));
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+/// Flattens the flows yielded by the main flow, cancelling the current one as soon
+/// as the next arrives.
+///
+/// A cancelled sub-flow can't be answered, so sub-flows only take `()`. An item not yet taken
+/// by the downstream when its sub-flow is replaced is dropped along with it.
+pub fn switch_all<'f, Y, In, Ret, M, Fl>(
+) -> WrapOp<impl FlowOp<Fl, In, Output = impl Flow<Yield = Y, Return = Ret> + 'f> + 'f>
+where
+    In: 'f + Default,
+    Y: 'f,
+    Ret: 'f,
+    M: 'f,
+    Fl: 'f + Flow<In, Return = Ret>,
+    Fl::Yield: IntoFlow<(), M, Yield = Y>,
+{
+    WrapOp(|main_flow: Fl| SwitchAll {
+        main_flow: main_flow.then(map_sync(IntoFlow::into_flow)),
+        sub_flow: None,
+        main_input: None,
+        sub_input: None,
+        item: None,
+        ret: None,
+    })
+}
+
+pin_project_lite::pin_project!(
+    pub struct Concat<Fl0, Fl1, Ret0> {
+        #[pin]
+        first: Fl0,
+        #[pin]
+        second: Fl1,
+        ret: Option<Ret0>,
+    }
+);
+
+impl<Fl0, Fl1, Y, Res> Flow<Res> for Concat<Fl0, Fl1, Fl0::Return>
+where
+    Fl0: Flow<Res, Yield = Y>,
+    Fl1: Flow<Res, Yield = Y>,
+    Res: Default,
+{
+    type Yield = Y;
+    type Return = (Fl0::Return, Fl1::Return);
+
+    fn poll_resume(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        input: &mut Option<Res>,
+    ) -> Poll<ControlFlow<Self::Return, Self::Yield>> {
+        let this = self.project();
+
+        if this.ret.is_none() {
+            let had_input = input.is_some();
+
+            match this.first.poll_resume(cx, input) {
+                Poll::Ready(Continue(yielded)) => return Poll::Ready(Continue(yielded)),
+                Poll::Ready(Break(ret)) => {
+                    *this.ret = Some(ret);
+                    if had_input && input.is_none() {
+                        *input = Some(Default::default());
+                    }
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        this.second.poll_resume(cx, input).map(|ctrl| match ctrl {
+            Continue(yielded) => Continue(yielded),
+            Break(ret) => Break((this.ret.take().unwrap(), ret)),
+        })
+    }
+
+    fn can_resume(self: Pin<&mut Self>) -> bool {
+        let this = self.project();
+        if this.ret.is_none() {
+            this.first.can_resume()
+        } else {
+            this.second.can_resume()
+        }
+    }
+}
+
+/// Plays `first` to completion, then `second`, returning both of their returns.
+pub fn concat<Res, Fl0, Fl1, M0, M1>(
+    first: impl IntoFlow<Res, M0, IntoFlow = Fl0>,
+    second: impl IntoFlow<Res, M1, IntoFlow = Fl1>,
+) -> Concat<Fl0, Fl1, Fl0::Return>
+where
+    Fl0: Flow<Res>,
+    Fl1: Flow<Res, Yield = Fl0::Yield>,
+{
+    Concat {
+        first: first.into_flow(),
+        second: second.into_flow(),
+        ret: None,
+    }
+}
+
+/// Continues with `next` once the source returns; see [`concat`].
+pub fn chain<'f, Res, Fl0, Fl1, M>(
+    next: impl IntoFlow<Res, M, IntoFlow = Fl1> + 'f,
+) -> WrapOp<impl FlowOp<Fl0, Res, Output = Concat<Fl0, Fl1, Fl0::Return>> + 'f>
+where
+    Fl0: 'f + Flow<Res>,
+    Fl1: 'f + Flow<Res, Yield = Fl0::Yield>,
+{
+    WrapOp(|first: Fl0| concat(first, next))
+}
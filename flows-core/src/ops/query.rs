@@ -0,0 +1,219 @@
+use std::{
+    future::Future,
+    ops::ControlFlow::{self, Break, Continue},
+    pin::Pin,
+    task::{self, Context, Poll},
+};
+
+use crate::{utils::map_future, Flow};
+
+use super::{FlowOp, WrapOp};
+
+pin_project_lite::pin_project!(
+    pub struct TryFold<Fl, Res, Acc, Fun> {
+        #[pin]
+        src: Fl,
+        input: Option<Res>,
+        acc: Option<Acc>,
+        fun: Fun,
+    }
+);
+
+impl<Fl, Res, Acc, Fun> Future for TryFold<Fl, Res, Acc, Fun>
+where
+    Fl: Flow<Res>,
+    Fun: FnMut(Acc, Fl::Yield) -> ControlFlow<Acc, Acc>,
+    Res: Default,
+{
+    type Output = (Acc, Option<Fl::Return>);
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        loop {
+            let this = self.as_mut().project();
+
+            match task::ready!(this.src.poll_resume(cx, this.input)) {
+                Continue(item) => match (this.fun)(this.acc.take().unwrap(), item) {
+                    Continue(acc) => {
+                        *this.acc = Some(acc);
+                        this.input.get_or_insert_with(Res::default);
+                    }
+                    Break(acc) => return Poll::Ready((acc, None)),
+                },
+                Break(ret) => return Poll::Ready((this.acc.take().unwrap(), Some(ret))),
+            }
+        }
+    }
+}
+
+/// Folds the source's items into `init`, stopping early once `fun` breaks.
+///
+/// The future's output holds the source's `Return` only if it ran to completion.
+pub fn try_fold<'f, Res, Fl, Acc, Fun>(
+    init: Acc,
+    fun: Fun,
+) -> WrapOp<impl FlowOp<Fl, Res, Output = TryFold<Fl, Res, Acc, Fun>> + 'f>
+where
+    Fl: 'f + Flow<Res>,
+    Fun: 'f + FnMut(Acc, Fl::Yield) -> ControlFlow<Acc, Acc>,
+    Acc: 'f,
+    Res: 'f + Default,
+{
+    WrapOp(|src: Fl| TryFold {
+        src,
+        input: Some(Res::default()),
+        acc: Some(init),
+        fun,
+    })
+}
+
+fn fold_all<'f, Res, Fl, Acc>(
+    init: Acc,
+    mut fun: impl 'f + FnMut(Acc, Fl::Yield) -> Acc,
+) -> WrapOp<impl FlowOp<Fl, Res, Output = impl Future<Output = (Acc, Fl::Return)> + 'f> + 'f>
+where
+    Fl: 'f + Flow<Res>,
+    Acc: 'f,
+    Res: 'f + Default,
+{
+    WrapOp(move |src: Fl| {
+        map_future(
+            src.then(try_fold(init, move |acc, item| Continue(fun(acc, item)))),
+            |(acc, ret): (Acc, Option<Fl::Return>)| (acc, ret.unwrap()),
+        )
+    })
+}
+
+/// Resolves to the first item, stopping the source there.
+#[allow(clippy::type_complexity)]
+pub fn first<'f, Res, Fl>() -> WrapOp<
+    impl FlowOp<Fl, Res, Output = impl Future<Output = (Option<Fl::Yield>, Option<Fl::Return>)> + 'f>
+        + 'f,
+>
+where
+    Fl: 'f + Flow<Res>,
+    Res: 'f + Default,
+{
+    find(|_| true)
+}
+
+/// Resolves to the `n`th item (counting from zero), stopping the source there.
+#[allow(clippy::type_complexity)]
+pub fn nth<'f, Res, Fl>(
+    n: usize,
+) -> WrapOp<
+    impl FlowOp<Fl, Res, Output = impl Future<Output = (Option<Fl::Yield>, Option<Fl::Return>)> + 'f>
+        + 'f,
+>
+where
+    Fl: 'f + Flow<Res>,
+    Res: 'f + Default,
+{
+    let mut remaining = n;
+    find(move |_| match remaining {
+        0 => true,
+        _ => {
+            remaining -= 1;
+            false
+        }
+    })
+}
+
+/// Resolves to the first item matching `pred`, stopping the source there.
+#[allow(clippy::type_complexity)]
+pub fn find<'f, Res, Fl>(
+    mut pred: impl 'f + FnMut(&Fl::Yield) -> bool,
+) -> WrapOp<
+    impl FlowOp<Fl, Res, Output = impl Future<Output = (Option<Fl::Yield>, Option<Fl::Return>)> + 'f>
+        + 'f,
+>
+where
+    Fl: 'f + Flow<Res>,
+    Res: 'f + Default,
+{
+    try_fold(None, move |_, item| match pred(&item) {
+        true => Break(Some(item)),
+        false => Continue(None),
+    })
+}
+
+/// Resolves to whether any item matches `pred`, stopping the source at the first match.
+pub fn any<'f, Res, Fl>(
+    mut pred: impl 'f + FnMut(&Fl::Yield) -> bool,
+) -> WrapOp<impl FlowOp<Fl, Res, Output = impl Future<Output = (bool, Option<Fl::Return>)> + 'f> + 'f>
+where
+    Fl: 'f + Flow<Res>,
+    Res: 'f + Default,
+{
+    try_fold(false, move |_, item| match pred(&item) {
+        true => Break(true),
+        false => Continue(false),
+    })
+}
+
+/// Resolves to whether every item matches `pred`, stopping the source at the first mismatch.
+pub fn all<'f, Res, Fl>(
+    mut pred: impl 'f + FnMut(&Fl::Yield) -> bool,
+) -> WrapOp<impl FlowOp<Fl, Res, Output = impl Future<Output = (bool, Option<Fl::Return>)> + 'f> + 'f>
+where
+    Fl: 'f + Flow<Res>,
+    Res: 'f + Default,
+{
+    try_fold(true, move |_, item| match pred(&item) {
+        true => Continue(true),
+        false => Break(false),
+    })
+}
+
+/// Resolves to the last item once the source returns.
+#[allow(clippy::type_complexity)]
+pub fn last<'f, Res, Fl>() -> WrapOp<
+    impl FlowOp<Fl, Res, Output = impl Future<Output = (Option<Fl::Yield>, Fl::Return)> + 'f> + 'f,
+>
+where
+    Fl: 'f + Flow<Res>,
+    Res: 'f + Default,
+{
+    fold_all(None, |_, item| Some(item))
+}
+
+/// Resolves to the number of items once the source returns.
+pub fn count<'f, Res, Fl>(
+) -> WrapOp<impl FlowOp<Fl, Res, Output = impl Future<Output = (usize, Fl::Return)> + 'f> + 'f>
+where
+    Fl: 'f + Flow<Res>,
+    Res: 'f + Default,
+{
+    fold_all(0, |count, _| count + 1)
+}
+
+/// Resolves to the smallest item once the source returns, preferring the first of equals.
+#[allow(clippy::type_complexity)]
+pub fn min<'f, Res, Fl>() -> WrapOp<
+    impl FlowOp<Fl, Res, Output = impl Future<Output = (Option<Fl::Yield>, Fl::Return)> + 'f> + 'f,
+>
+where
+    Fl: 'f + Flow<Res>,
+    Fl::Yield: Ord,
+    Res: 'f + Default,
+{
+    fold_all(None, |min, item| match min {
+        Some(min) if min <= item => Some(min),
+        _ => Some(item),
+    })
+}
+
+/// Resolves to the largest item once the source returns, preferring the last of equals.
+#[allow(clippy::type_complexity)]
+pub fn max<'f, Res, Fl>() -> WrapOp<
+    impl FlowOp<Fl, Res, Output = impl Future<Output = (Option<Fl::Yield>, Fl::Return)> + 'f> + 'f,
+>
+where
+    Fl: 'f + Flow<Res>,
+    Fl::Yield: Ord,
+    Res: 'f + Default,
+{
+    fold_all(None, |max, item| match max {
+        Some(max) if max > item => Some(max),
+        _ => Some(item),
+    })
+}
@@ -0,0 +1,306 @@
+use std::{
+    ops::ControlFlow::{self, Break, Continue},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::{convert::IntoFlow, Flow};
+
+use super::{FlowOp, WrapOp};
+
+pin_project_lite::pin_project!(
+    /// Zips a tuple of flows, yielding a tuple once every flow has yielded.
+    ///
+    /// `Rs` and `Ys` are tuples of `Option`s holding each flow's pending input and yield.
+    pub struct Zip<Fs, Rs, Ys> {
+        #[pin]
+        flows: Fs,
+        res: Rs,
+        yld: Ys,
+    }
+);
+
+impl<Fs, Rs: Default, Ys: Default> Zip<Fs, Rs, Ys> {
+    pub fn new(flows: Fs) -> Self {
+        Self {
+            flows,
+            res: Default::default(),
+            yld: Default::default(),
+        }
+    }
+}
+
+macro_rules! impl_zip {
+    ($(($F:ident, $R:ident, $Y:ident, $T:ident, $i:tt)),+) => {
+        impl<Ret, $($F, $R, $Y),+> Flow<($($R,)+)>
+            for Zip<($($F,)+), ($(Option<$R>,)+), ($(Option<$Y>,)+)>
+        where
+            $($F: Flow<$R, Yield = $Y, Return = Ret>,)+
+        {
+            type Yield = ($($Y,)+);
+            type Return = Ret;
+
+            fn poll_resume(
+                self: Pin<&mut Self>,
+                cx: &mut Context,
+                input: &mut Option<($($R,)+)>,
+            ) -> Poll<ControlFlow<Self::Return, Self::Yield>> {
+                let this = self.project();
+                // SAFETY: the flows are never moved out of the tuple, so pinning it pins them.
+                let flows = unsafe { this.flows.get_unchecked_mut() };
+                let had_input = input.is_some();
+
+                if let Some(resumed) = input.take() {
+                    $(this.res.$i = Some(resumed.$i);)+
+                }
+
+                $(
+                    if this.yld.$i.is_none() {
+                        let flow = unsafe { Pin::new_unchecked(&mut flows.$i) };
+                        match flow.poll_resume(cx, &mut this.res.$i) {
+                            Poll::Ready(Continue(yielded)) => this.yld.$i = Some(yielded),
+                            Poll::Ready(Break(ret)) => return Poll::Ready(Break(ret)),
+                            Poll::Pending => {}
+                        }
+                    }
+                )+
+
+                if had_input && $(this.res.$i.is_some())&&+ {
+                    *input = Some(($(this.res.$i.take().unwrap(),)+));
+                }
+
+                if $(this.yld.$i.is_some())&&+ {
+                    Poll::Ready(Continue(($(this.yld.$i.take().unwrap(),)+)))
+                } else {
+                    Poll::Pending
+                }
+            }
+
+            fn can_resume(self: Pin<&mut Self>) -> bool {
+                let flows = unsafe { self.project().flows.get_unchecked_mut() };
+                $(unsafe { Pin::new_unchecked(&mut flows.$i) }.can_resume())||+
+            }
+        }
+    };
+}
+
+for_tuples!(impl_zip);
+
+pub type Zip2<F0, F1, R0, R1, Y0, Y1> =
+    Zip<(F0, F1), (Option<R0>, Option<R1>), (Option<Y0>, Option<Y1>)>;
+
+#[allow(clippy::type_complexity)]
+pub fn zip_with<Res0, Res1, Fl0, Fl1, M>(
+    rhs: impl IntoFlow<Res1, M, IntoFlow = Fl1>,
+) -> WrapOp<impl FlowOp<Fl0, Res0, Output = Zip2<Fl0, Fl1, Res0, Res1, Fl0::Yield, Fl1::Yield>>>
+where
+    Fl0: Flow<Res0>,
+    Fl1: Flow<Res1>,
+{
+    WrapOp(|lhs| zip(lhs, rhs))
+}
+
+pub fn zip<Res0, Res1, Fl0, Fl1, M0, M1>(
+    f0: impl IntoFlow<Res0, M0, IntoFlow = Fl0>,
+    f1: impl IntoFlow<Res1, M1, IntoFlow = Fl1>,
+) -> Zip2<Fl0, Fl1, Res0, Res1, Fl0::Yield, Fl1::Yield>
+where
+    Fl0: Flow<Res0>,
+    Fl1: Flow<Res1>,
+{
+    Zip::new((f0.into_flow(), f1.into_flow()))
+}
+
+/// Zips a collection of flows of the same type, yielding a `Vec` once every flow has yielded.
+///
+/// The downstream input answers an item made of every flow's yield, so it's cloned to every
+/// flow.
+pub struct ZipAll<Fl: Flow<Res>, Res> {
+    flows: Vec<Pin<Box<Fl>>>,
+    res: Vec<Option<Res>>,
+    yld: Vec<Option<Fl::Yield>>,
+}
+
+impl<Fl: Flow<Res>, Res> Unpin for ZipAll<Fl, Res> {}
+
+impl<Fl: Flow<Res>, Res: Clone> Flow<Res> for ZipAll<Fl, Res> {
+    type Yield = Vec<Fl::Yield>;
+    type Return = Fl::Return;
+
+    fn poll_resume(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        input: &mut Option<Res>,
+    ) -> Poll<ControlFlow<Self::Return, Self::Yield>> {
+        let this = self.get_mut();
+        let had_input = input.is_some();
+
+        if let Some(resumed) = input.take() {
+            this.res.fill(Some(resumed));
+        }
+
+        for ((flow, res), yld) in this.flows.iter_mut().zip(&mut this.res).zip(&mut this.yld) {
+            if yld.is_none() {
+                match flow.as_mut().poll_resume(cx, res) {
+                    Poll::Ready(Continue(yielded)) => *yld = Some(yielded),
+                    Poll::Ready(Break(ret)) => return Poll::Ready(Break(ret)),
+                    Poll::Pending => {}
+                }
+            }
+        }
+
+        if had_input && this.res.iter().all(Option::is_some) {
+            *input = this.res[0].take();
+            this.res.fill(None);
+        }
+
+        if this.yld.iter().all(Option::is_some) {
+            Poll::Ready(Continue(
+                this.yld.iter_mut().map(|yld| yld.take().unwrap()).collect(),
+            ))
+        } else {
+            Poll::Pending
+        }
+    }
+
+    fn can_resume(self: Pin<&mut Self>) -> bool {
+        self.get_mut()
+            .flows
+            .iter_mut()
+            .any(|flow| flow.as_mut().can_resume())
+    }
+}
+
+/// Zips a collection of flows of the same type.
+///
+/// # Panics
+///
+/// Panics if `flows` is empty, since there'd be nothing to return.
+pub fn zip_all<Res, Fl, M>(
+    flows: impl IntoIterator<Item = impl IntoFlow<Res, M, IntoFlow = Fl>>,
+) -> ZipAll<Fl, Res>
+where
+    Fl: Flow<Res>,
+{
+    let flows: Vec<_> = flows
+        .into_iter()
+        .map(|flow| Box::pin(flow.into_flow()))
+        .collect();
+    assert!(!flows.is_empty(), "zip_all requires at least one flow");
+
+    ZipAll {
+        res: flows.iter().map(|_| None).collect(),
+        yld: flows.iter().map(|_| None).collect(),
+        flows,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EitherOrBoth<L, R> {
+    Left(L),
+    Right(R),
+    Both(L, R),
+}
+
+pin_project_lite::pin_project!(
+    pub struct ZipLongest<F0, F1, R0, R1, Y0, Y1, Ret0, Ret1> {
+        #[pin]
+        f0: F0,
+        #[pin]
+        f1: F1,
+        res: (Option<R0>, Option<R1>),
+        yld: (Option<Y0>, Option<Y1>),
+        ret: (Option<Ret0>, Option<Ret1>),
+    }
+);
+
+impl<F0, F1, R0, R1, Y0, Y1, Ret0, Ret1> Flow<(R0, R1)>
+    for ZipLongest<F0, F1, R0, R1, Y0, Y1, Ret0, Ret1>
+where
+    F0: Flow<R0, Yield = Y0, Return = Ret0>,
+    F1: Flow<R1, Yield = Y1, Return = Ret1>,
+{
+    type Yield = EitherOrBoth<Y0, Y1>;
+    type Return = (Ret0, Ret1);
+
+    fn poll_resume(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        input: &mut Option<(R0, R1)>,
+    ) -> Poll<ControlFlow<Self::Return, Self::Yield>> {
+        let this = self.project();
+        let had_input = input.is_some();
+
+        if let Some((r0, r1)) = input.take() {
+            *this.res = (Some(r0), Some(r1));
+        }
+
+        if this.ret.0.is_none() && this.yld.0.is_none() {
+            match this.f0.poll_resume(cx, &mut this.res.0) {
+                Poll::Ready(Continue(yielded)) => this.yld.0 = Some(yielded),
+                Poll::Ready(Break(ret)) => this.ret.0 = Some(ret),
+                Poll::Pending => {}
+            }
+        }
+
+        if this.ret.1.is_none() && this.yld.1.is_none() {
+            match this.f1.poll_resume(cx, &mut this.res.1) {
+                Poll::Ready(Continue(yielded)) => this.yld.1 = Some(yielded),
+                Poll::Ready(Break(ret)) => this.ret.1 = Some(ret),
+                Poll::Pending => {}
+            }
+        }
+
+        if had_input {
+            if let (Some(_), Some(_)) = this.res {
+                *input = Some((this.res.0.take().unwrap(), this.res.1.take().unwrap()));
+            }
+        }
+
+        let done = (this.ret.0.is_some(), this.ret.1.is_some());
+
+        match (&mut *this.yld, done) {
+            ((Some(_), Some(_)), _) => Poll::Ready(Continue(EitherOrBoth::Both(
+                this.yld.0.take().unwrap(),
+                this.yld.1.take().unwrap(),
+            ))),
+            ((Some(_), None), (_, true)) => {
+                Poll::Ready(Continue(EitherOrBoth::Left(this.yld.0.take().unwrap())))
+            }
+            ((None, Some(_)), (true, _)) => {
+                Poll::Ready(Continue(EitherOrBoth::Right(this.yld.1.take().unwrap())))
+            }
+            ((None, None), (true, true)) => Poll::Ready(Break((
+                this.ret.0.take().unwrap(),
+                this.ret.1.take().unwrap(),
+            ))),
+            _ => Poll::Pending,
+        }
+    }
+
+    fn can_resume(self: Pin<&mut Self>) -> bool {
+        let this = self.project();
+        (this.ret.0.is_none() && this.f0.can_resume())
+            || (this.ret.1.is_none() && this.f1.can_resume())
+    }
+}
+
+/// Like [`zip`], but keeps going until both flows return, yielding
+/// [`EitherOrBoth::Left`] or [`EitherOrBoth::Right`] once the other side is done.
+#[allow(clippy::type_complexity)]
+pub fn zip_longest<Res0, Res1, Fl0, Fl1, M0, M1>(
+    f0: impl IntoFlow<Res0, M0, IntoFlow = Fl0>,
+    f1: impl IntoFlow<Res1, M1, IntoFlow = Fl1>,
+) -> ZipLongest<Fl0, Fl1, Res0, Res1, Fl0::Yield, Fl1::Yield, Fl0::Return, Fl1::Return>
+where
+    Fl0: Flow<Res0>,
+    Fl1: Flow<Res1>,
+{
+    ZipLongest {
+        f0: f0.into_flow(),
+        f1: f1.into_flow(),
+        res: (None, None),
+        yld: (None, None),
+        ret: (None, None),
+    }
+}
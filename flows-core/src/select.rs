@@ -0,0 +1,90 @@
+use std::{
+    ops::ControlFlow::{self, Break, Continue},
+    pin::Pin,
+    task::{self, Context, Poll},
+};
+
+use futures_util::future::FusedFuture;
+
+use super::Flow;
+
+/// Something `select_flow!` can wait on: a [`Selectable`] flow or a [`FusedFuture`].
+pub trait SelectBranch {
+    type Output;
+
+    fn poll_branch(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output>;
+
+    /// Whether the branch has finished and must not be polled again.
+    fn is_terminated(&self) -> bool;
+}
+
+impl<Fut> SelectBranch for Fut
+where
+    Fut: ?Sized + FusedFuture,
+{
+    type Output = Fut::Output;
+
+    fn poll_branch(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        self.poll(cx)
+    }
+
+    fn is_terminated(&self) -> bool {
+        FusedFuture::is_terminated(self)
+    }
+}
+
+pin_project_lite::pin_project!(
+    /// Wraps a flow so `select_flow!` can wait for its next item across loop iterations.
+    ///
+    /// The flow is resumed with `Res::default()` whenever it's due another item. A resume
+    /// that another branch won the race against stays with the flow, so no item is lost
+    /// or requested twice. Each firing is `Continue(item)`, or `Break(ret)` once, after
+    /// which the branch is terminated.
+    pub struct Selectable<Fl, Res = ()> {
+        #[pin]
+        flow: Fl,
+        input: Option<Res>,
+        done: bool,
+    }
+);
+
+impl<Fl, Res> Selectable<Fl, Res>
+where
+    Fl: Flow<Res>,
+    Res: Default,
+{
+    pub fn new(flow: Fl) -> Self {
+        Selectable {
+            flow,
+            input: Some(Res::default()),
+            done: false,
+        }
+    }
+}
+
+impl<Fl, Res> SelectBranch for Selectable<Fl, Res>
+where
+    Fl: Flow<Res>,
+    Res: Default,
+{
+    type Output = ControlFlow<Fl::Return, Fl::Yield>;
+
+    fn poll_branch(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.project();
+
+        match task::ready!(this.flow.poll_resume(cx, this.input)) {
+            Continue(item) => {
+                this.input.get_or_insert_with(Res::default);
+                Poll::Ready(Continue(item))
+            }
+            Break(ret) => {
+                *this.done = true;
+                Poll::Ready(Break(ret))
+            }
+        }
+    }
+
+    fn is_terminated(&self) -> bool {
+        self.done
+    }
+}
@@ -18,7 +18,7 @@ pub struct SelfRef<'x, T: ?Sized, R: ?Sized + for<'lt> WithLifetime<'lt>> {
     pub target: T,
 }
 
-impl<'x, T: ?Sized, R: ?Sized + for<'lt> WithLifetime<'lt>> Default for SelfRef<'x, T, R>
+impl<'x, T, R: ?Sized + for<'lt> WithLifetime<'lt>> Default for SelfRef<'x, T, R>
 where
     T: Default,
     for<'lt> Actual<'lt, R>: Default,
@@ -49,7 +49,7 @@ impl<'x, T: ?Sized, R: ?Sized + for<'lt> WithLifetime<'lt>> SelfRef<'x, T, R> {
         }
     }
 
-    pub fn reference(&self) -> &Actual<R> {
+    pub fn reference(&self) -> &Actual<'_, R> {
         unsafe { &*(&self.reference as *const Actual<'static, R>).cast() }
     }
 
@@ -7,9 +7,7 @@ use std::{
 
 use crate::custom_fn::MapFnOnce;
 
-pub(crate) fn pin_as_deref_mut<'ptr, P: DerefMut>(
-    pin: Pin<&'ptr mut Pin<P>>,
-) -> Pin<&'ptr mut P::Target> {
+pub(crate) fn pin_as_deref_mut<P: DerefMut>(pin: Pin<&mut Pin<P>>) -> Pin<&mut P::Target> {
     unsafe { pin.get_unchecked_mut() }.as_mut()
 }
 
@@ -93,7 +93,10 @@ macro_rules! _flow {
         $crate::_flow! { $(#$attr)* $($move)? |$input : $input_ty, (mut sender): $crate::flows::Sender<_, $input_ty>| -> $ret {
             $crate::_flow_local_macros! { ($) sender, $rest }
         } }
-    }
+    };
+    { $($x:tt)* } => {
+        ::core::compile_error!("`flow!` expects a closure taking at most the first resume value and a sender, like `|| ...`, `|first| ...` or `|first, sender| ...`")
+    };
 }
 
 #[macro_export]
@@ -103,6 +106,247 @@ macro_rules! flow {
     };
 }
 
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _try_flow {
+    { $(#$attr:tt)* $($move:ident)? | | $($rest:tt)* } => {
+        $crate::_try_flow! { $(#$attr)* $($move)? |(): ()| $($rest)* }
+    };
+    {
+        $(#$attr:tt)*
+        $($move:ident)? |
+            $input:tt : $input_ty:ty
+            $(, $sender:tt : $sender_ty:ty)?
+        | -> _ $rest:block
+    } => {
+        ::core::compile_error!("`try_flow!` needs its return type spelled out, like `|| -> Result<_, MyError> { ... }`")
+    };
+    {
+        $(#$attr:tt)*
+        $($move:ident)? |
+            $input:tt : $input_ty:ty
+            $(, $sender:tt : $sender_ty:ty)?
+        | -> $ret:ty $rest:block
+    } => {
+        $crate::_flow! {
+            $(#$attr)* $($move)? |$input: $input_ty $(, $sender: $sender_ty)?| -> $ret {
+                $crate::flows::my_try::MyTry::from_continue($rest)
+            }
+        }
+    };
+    { $($x:tt)* } => {
+        ::core::compile_error!("`try_flow!` expects a closure taking at most the first resume value and a sender, like `|| ...`, `|first| ...` or `|first, sender| ...`")
+    };
+}
+
+/// Like `flow!`, but the body's value is wrapped with `MyTry::from_continue`, so `?` can
+/// be used on anything the return type accepts, such as a failed `next_from!`.
+///
+/// The return type must be spelled out, e.g. `|| -> Result<_, MyError> { ... }`. Use
+/// `try_for_each!(#[flatten] ...)` or `try_transform_each!(#[flatten] ...)` on the
+/// resulting flow so its failure comes back alongside the closure's.
+#[macro_export]
+macro_rules! try_flow {
+    { $($x:tt)* } => {
+        $crate::normalize_op_input! { ($crate::_try_flow) {} $($x)* }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _one_of {
+    (@arms ($scrutinee:expr) [$($done:tt)*] [$($used:ident)*] [$v:ident $($vs:ident)*] $pat:pat if $guard:expr => $body:expr $(, $($rest:tt)*)?) => {
+        $crate::_one_of! {
+            @arms ($scrutinee) [$($done)* ([$guard] $pat => $v $body)] [$($used)* $v] [$($vs)*] $($($rest)*)?
+        }
+    };
+    (@arms ($scrutinee:expr) [$($done:tt)*] [$($used:ident)*] [$v:ident $($vs:ident)*] $pat:pat => $body:expr $(, $($rest:tt)*)?) => {
+        $crate::_one_of! {
+            @arms ($scrutinee) [$($done)* ([] $pat => $v $body)] [$($used)* $v] [$($vs)*] $($($rest)*)?
+        }
+    };
+    (@arms ($scrutinee:expr) $done:tt $used:tt [] $($rest:tt)+) => {
+        ::core::compile_error!("`one_of!` supports at most 8 arms; nest another `one_of!` in the last one for more")
+    };
+    (@arms ($scrutinee:expr) $done:tt [$($used:ident $($more:ident)?)?] $unused:tt) => {
+        ::core::compile_error!("`one_of!` needs at least 3 arms; use `Either` to pick between two flows")
+    };
+    (@arms ($scrutinee:expr) [$(([$($guard:expr)?] $pat:pat => $v:ident $body:expr))*] $used:tt $unused:tt) => {
+        match $scrutinee {
+            $($pat $(if $guard)? => $crate::_one_of!(@wrap $used $v $body),)*
+        }
+    };
+    (@wrap [A B C] $v:ident $body:expr) => { $crate::flows::one_of::OneOf3::$v { flow: $body } };
+    (@wrap [A B C D] $v:ident $body:expr) => { $crate::flows::one_of::OneOf4::$v { flow: $body } };
+    (@wrap [A B C D E] $v:ident $body:expr) => { $crate::flows::one_of::OneOf5::$v { flow: $body } };
+    (@wrap [A B C D E F] $v:ident $body:expr) => { $crate::flows::one_of::OneOf6::$v { flow: $body } };
+    (@wrap [A B C D E F G] $v:ident $body:expr) => { $crate::flows::one_of::OneOf7::$v { flow: $body } };
+    (@wrap [A B C D E F G H] $v:ident $body:expr) => { $crate::flows::one_of::OneOf8::$v { flow: $body } };
+}
+
+/// Picks one of 3 to 8 flows with a `match`, wrapping each arm in the matching `OneOfN`
+/// variant so the arms can have different types:
+/// `one_of!(key; 0 => flow_a, n if n < 10 => flow_b, _ => flow_c)`.
+#[macro_export]
+macro_rules! one_of {
+    ($scrutinee:expr; $($arms:tt)+) => {
+        $crate::_one_of! { @arms ($scrutinee) [] [] [A B C D E F G H] $($arms)+ }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _select_flow {
+    (@parse $arms:tt []) => {
+        $crate::_select_flow! { @expand $arms [] }
+    };
+    (@parse $arms:tt [$complete:expr]) => {
+        $crate::_select_flow! { @expand $arms [$complete] }
+    };
+    (@parse $arms:tt [] complete => $body:block , $($rest:tt)*) => {
+        $crate::_select_flow! { @parse $arms [$body] $($rest)* }
+    };
+    (@parse $arms:tt [] complete => $body:block $($rest:tt)*) => {
+        $crate::_select_flow! { @parse $arms [$body] $($rest)* }
+    };
+    (@parse $arms:tt [] complete => $body:expr $(, $($rest:tt)*)?) => {
+        $crate::_select_flow! { @parse $arms [$body] $($($rest)*)? }
+    };
+    (@parse [$($arms:tt)*] $complete:tt $pat:pat = $src:expr => $body:block , $($rest:tt)*) => {
+        $crate::_select_flow! { @parse [$($arms)* (branch out $pat, $src, $body)] $complete $($rest)* }
+    };
+    (@parse [$($arms:tt)*] $complete:tt $pat:pat = $src:expr => $body:block $($rest:tt)*) => {
+        $crate::_select_flow! { @parse [$($arms)* (branch out $pat, $src, $body)] $complete $($rest)* }
+    };
+    (@parse [$($arms:tt)*] $complete:tt $pat:pat = $src:expr => $body:expr $(, $($rest:tt)*)?) => {
+        $crate::_select_flow! { @parse [$($arms)* (branch out $pat, $src, $body)] $complete $($($rest)*)? }
+    };
+    // `branch` and `out` come from a different expansion for each arm, so hygiene keeps
+    // them apart.
+    (@expand [$(($branch:ident $out:ident $pat:pat, $src:expr, $body:expr))*] [$($complete:expr)?]) => {{
+        $(
+            let $branch = &mut $src;
+            let mut $out = ::core::option::Option::None;
+        )*
+        ::core::future::poll_fn(|cx| {
+            let mut live = false;
+            $(
+                if !$crate::flows::select::SelectBranch::is_terminated(&*$branch) {
+                    live = true;
+                    if let ::core::task::Poll::Ready(value) =
+                        $crate::flows::select::SelectBranch::poll_branch(::core::pin::Pin::new(&mut *$branch), cx)
+                    {
+                        $out = ::core::option::Option::Some(value);
+                        return ::core::task::Poll::Ready(());
+                    }
+                }
+            )*
+            match live {
+                true => ::core::task::Poll::Pending,
+                false => ::core::task::Poll::Ready(()),
+            }
+        })
+        .await;
+
+        $(
+            if let ::core::option::Option::Some(value) = $out {
+                let $pat = value;
+                $body
+            } else
+        )* {
+            $crate::_select_flow! { @complete $($complete)? }
+        }
+    }};
+    (@complete $complete:expr) => {
+        $complete
+    };
+    (@complete) => {
+        ::core::panic!("all branches of `select_flow!` are terminated and there is no `complete` arm")
+    };
+}
+
+/// Waits until one of several flows or futures fires, then runs its arm. For use in
+/// async bodies such as `flow!`'s, typically in a loop:
+///
+/// ```ignore
+/// let mut data = Selectable::new(data);
+/// let mut cancel = pin!(cancel.fuse());
+/// loop {
+///     select_flow! {
+///         item = data => match item {
+///             Continue(x) => next!(x),
+///             Break(()) => break,
+///         },
+///         () = cancel => break,
+///         complete => break,
+///     }
+/// }
+/// ```
+///
+/// Each arm is `pattern = branch => body`, where the branch is an `Unpin`
+/// [`Selectable`](flows_core::select::Selectable) flow, which fires with its next
+/// `ControlFlow`, or an `Unpin` `FusedFuture`. Patterns must be irrefutable. Branches are
+/// polled in order and borrowed rather than consumed, so the ones that didn't fire keep
+/// their progress for the next iteration. Terminated branches are skipped; once all of
+/// them are, the `complete` arm runs, or the macro panics if there is none.
+#[macro_export]
+macro_rules! select_flow {
+    { $($arms:tt)* } => {
+        $crate::_select_flow! { @parse [] [] $($arms)* }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _channel_flow {
+    {
+        ($new:expr, $capacity:expr)
+        $(#[capture $cap:tt])*
+        $($move:ident)? |$producer:tt : $producer_ty:ty| -> $ret:ty $rest:block
+    } => {{
+        $crate::capture_outer! { [$($cap)*] }
+        $new($capacity, $($move)? |_producer: $crate::flows::channel::ProducerHandle<_>| {
+            let _producer = $crate::macro_utils::NotCopy(_producer);
+            async $($move)? {
+                let _producer = _producer;
+                #[allow(unused_parens)]
+                let $producer: $producer_ty = _producer.0;
+
+                $crate::capture_inner! { [$($cap)*] }
+
+                let ret: $ret = $rest;
+                ret
+            }
+        })
+    }};
+}
+
+#[macro_export]
+macro_rules! channel_flow {
+    { capacity = $capacity:expr, $($x:tt)* } => {
+        $crate::normalize_op_input! {
+            ($crate::_channel_flow) { ($crate::flows::channel::channel_flow_from_fn, $capacity) }
+            $($x)*
+        }
+    };
+    { $($x:tt)* } => {
+        $crate::channel_flow! { capacity = $crate::flows::channel::DEFAULT_CAPACITY, $($x)* }
+    };
+}
+
+#[macro_export]
+macro_rules! callback_flow {
+    { capacity = $capacity:expr, $($x:tt)* } => {
+        $crate::normalize_op_input! {
+            ($crate::_channel_flow) { ($crate::flows::channel::callback_flow_from_fn, $capacity) }
+            $($x)*
+        }
+    };
+    { $($x:tt)* } => {
+        $crate::callback_flow! { capacity = $crate::flows::channel::DEFAULT_CAPACITY, $($x)* }
+    };
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! _collector {
@@ -79,6 +79,43 @@ macro_rules! last_ident {
     ($a:ident $b:ident $c:ident $($d:ident)+) => { $crate::last_ident!($($d)+) };
 }
 
+/// Expands to the block if every `#[capture(...)]` entry has a form `capture_outer!` and
+/// `capture_inner!` understand, or to a single error otherwise.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! check_capture {
+    { [$(,)?] { $($then:tt)* } } => { $($then)* };
+
+    { [ref $(, $($rest:tt)*)?] $then:tt } => { $crate::check_capture!(@missing_name ref) };
+    { [mut $(, $($rest:tt)*)?] $then:tt } => { $crate::check_capture!(@missing_name mut) };
+    { [ref mut $(, $($rest:tt)*)?] $then:tt } => { $crate::check_capture!(@missing_name ref mut) };
+    { [$a:ident ref $(, $($rest:tt)*)?] $then:tt } => { $crate::check_capture!(@missing_name $a ref) };
+    { [$a:ident mut $(, $($rest:tt)*)?] $then:tt } => { $crate::check_capture!(@missing_name $a mut) };
+    { [$a:ident $b:ident ref $(, $($rest:tt)*)?] $then:tt } => {
+        $crate::check_capture!(@missing_name $a $b ref)
+    };
+    { [$a:ident $b:ident mut $(, $($rest:tt)*)?] $then:tt } => {
+        $crate::check_capture!(@missing_name $a $b mut)
+    };
+
+    { [$a:ident $($b:ident $($c:ident)?)? $(, $($rest:tt)*)?] $then:tt } => {
+        $crate::check_capture! { [$($($rest)*)?] $then }
+    };
+    { [$($rest:tt)*] $then:tt } => {
+        ::core::compile_error!(::core::concat!(
+            "unexpected `", ::core::stringify!($($rest)*), "` in `#[capture(...)]`; ",
+            "each entry is a variable, optionally preceded by `ref`, `ref mut`, `mut` ",
+            "or a method such as `clone`"
+        ))
+    };
+
+    (@missing_name $($entry:ident)+) => {
+        ::core::compile_error!(::core::concat!(
+            "`#[capture(", ::core::stringify!($($entry)+), ")]` is missing the variable name"
+        ))
+    };
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! capture_outer {
@@ -138,3 +175,39 @@ macro_rules! capture_inner {
         $crate::capture_inner! { @capture_assign $cap }
     };
 }
+
+/// Re-runs the captures made by `capture_outer!` for each call of an op's closure, so every
+/// future the closure returns gets its own copy.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! capture_per_call {
+    { @capture_assign [$($cap:tt)*] } => {
+        $( $crate::capture_per_call! { @capture_assign $cap } )*
+    };
+
+    { @capture_assign { outer: $cap:tt } } => {
+        $crate::capture_per_call! { @capture_assign $cap }
+    };
+
+    { @capture_assign { inner: $cap:tt } } => {};
+
+    { @capture_assign ($( $($var:ident)+ ),* $(,)?) } => { $(
+        let $crate::last_ident!($($var)+) = $crate::macro_utils::NotCopy(
+            $crate::capture_per_call!(@capture_call_val $($var)+)
+        );
+    )* };
+
+    { @capture_call_val ref mut $name:ident } => {
+        ::core::compile_error!(::core::concat!(
+            "`ref mut ", ::core::stringify!($name), "` can't be shared between concurrent futures"
+        ))
+    };
+    { @capture_call_val ref $name:ident } => { $name.0 };
+    { @capture_call_val mut $name:ident } => { $name.0 };
+    { @capture_call_val $name:ident } => { $name.0 };
+    { @capture_call_val $op:ident $name:ident } => { $name.0 . $op () };
+
+    { $cap:tt } => {
+        $crate::capture_per_call! { @capture_assign $cap }
+    };
+}
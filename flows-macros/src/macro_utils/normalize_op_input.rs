@@ -1,6 +1,9 @@
 #[doc(hidden)]
 #[macro_export]
 macro_rules! normalize_op_input {
+    { @fn $out:tt { $($pre:tt)* } | { $($arg0:tt : $arg0_ty:ty),* } | -> { $($body:tt)* } $($rest:tt)* } => {
+        ::core::compile_error!("expected a type after `->`; leave the arrow out to have it inferred")
+    };
     { @fn $out:tt { $($pre:tt)* } | { $($arg0:tt : $arg0_ty:ty),* } | -> $return_ty:ty { $($body:tt)* } $($rest:tt)* } => {
         $crate::normalize_op_input! {
             $out { $($pre)* | $( $arg0: $arg0_ty ),* | -> $return_ty {$($body)*} }
@@ -107,12 +110,38 @@ macro_rules! normalize_op_input {
         }
     };
 
-    { $out:tt { $($pre:tt)* } #[$($attr:tt)*] $($rest:tt)* } => {
+    { $out:tt { $($pre:tt)* } #[capture ($($cap:tt)*)] $($rest:tt)* } => {
+        $crate::check_capture! {
+            [$($cap)*]
+            {
+                $crate::normalize_op_input! {
+                    $out { $($pre)* #[capture ($($cap)*)] }
+                    $($rest)*
+                }
+            }
+        }
+    };
+    { $out:tt { $($pre:tt)* } #[capture { $($cap:tt)* }] $($rest:tt)* } => {
         $crate::normalize_op_input! {
-            $out { $($pre)* #[$($attr)*] }
+            $out { $($pre)* #[capture { $($cap)* }] }
             $($rest)*
         }
     };
+    { $out:tt { $($pre:tt)* } #[capture $($cap:tt)*] $($rest:tt)* } => {
+        ::core::compile_error!(
+            "expected a list of variables in parentheses, like `#[capture(ref mut out, clone name)]`"
+        )
+    };
+    { $out:tt { $($pre:tt)* } #[flatten] $($rest:tt)* } => {
+        ::core::compile_error!(
+            "`#[flatten]` is only supported as the first attribute of `try_for_each!` or `try_transform_each!`"
+        )
+    };
+    { $out:tt { $($pre:tt)* } #[$name:ident $($attr:tt)*] $($rest:tt)* } => {
+        ::core::compile_error!(::core::concat!(
+            "unknown attribute `", ::core::stringify!($name), "`; only `#[capture(...)]` is supported here"
+        ))
+    };
 
     { $out:tt { $($pre:tt)* } $($x:ident)+ $(, $($rest:tt)*)? } => {
         $crate::normalize_op_input! {
@@ -1,6 +1,9 @@
 #[doc(hidden)]
 #[macro_export]
 macro_rules! _try_for_each {
+    { #[flatten] $($rest:tt)* } => {
+        $crate::flows::ops::flatten_try($crate::_try_for_each! { $($rest)* })
+    };
     {
         $(#[capture $cap:tt])*
         $($move:ident)? |$input:tt : $input_ty:ty| -> $resume:ty $rest:block,
@@ -30,10 +33,22 @@ macro_rules! _try_for_each {
             $($move)? |$input: $input_ty| -> $resume $rest
         }))
     }};
+    { $($x:tt)* } => {
+        ::core::compile_error!("`try_for_each!` expects a closure taking one item, like `|item| ...`")
+    };
 }
 
+/// Drives the source, resuming it with each `Continue` the closure returns and stopping
+/// at the first `Break`.
+///
+/// Starting with `#[flatten]` merges a source's own `MyTry` return, such as a
+/// `try_flow!`'s, into the result instead of nesting it. It's opt-in because without
+/// specialization the op can't tell that apart from a source returning a plain value.
 #[macro_export]
 macro_rules! try_for_each {
+    { #[flatten] $($x:tt)* } => {
+        $crate::normalize_op_input! { ($crate::_try_for_each) { #[flatten] } $($x)* }
+    };
     { $($x:tt)* } => {
         $crate::normalize_op_input! { ($crate::_try_for_each) {} $($x)* }
     };
@@ -71,6 +86,9 @@ macro_rules! _for_each {
             $($move)? |$input: $input_ty| -> $resume $rest
         }))
     }};
+    { $($x:tt)* } => {
+        ::core::compile_error!("`for_each!` expects a closure taking one item, like `|item| ...`")
+    };
 }
 
 #[macro_export]
@@ -83,21 +101,22 @@ macro_rules! for_each {
 #[doc(hidden)]
 #[macro_export]
 macro_rules! _try_transform_each {
+    { #[flatten] $($rest:tt)* } => {
+        $crate::_try_transform_each! { @op [_flow_transform_op _ _ _ _] [#[flatten]] $($rest)* }
+    };
     {
+        @op [$op:ident $($holes:tt)*] [$($flatten:tt)*]
         $(#[capture $cap:tt])*
         $($move:ident)? |$input:tt : $input_ty:ty, $($sender1:ident)? $((mut $sender2:ident))? : $sender_ty:ty| -> $resume:ty $rest:block
     } => {{
         $crate::capture_outer! { [$($cap)*] }
-        $crate::macro_utils::_flow_try_transform_op::<
-            $input_ty, $resume, _,
-            _, _,
-            _, _,
-        >($($move)? |src| {
+        $crate::macro_utils::$op::<$input_ty, $resume, $($holes),*>($($move)? |src| {
             let src = $crate::macro_utils::NotCopy(src);
             $crate::_flow! {
                 $($move)? |_transform_resume: $resume, $($sender1)? $($sender2)?: $sender_ty| -> _ {
                     let src = src;
                     $crate::_try_for_each!(
+                        $($flatten)*
                         #[capture($($sender1)? $(mut $sender2)?)]
                         $(#[capture { inner: $cap }])*
                         $($move)? |$input: $input_ty| -> $resume {
@@ -110,19 +129,32 @@ macro_rules! _try_transform_each {
         })
     }};
     {
+        @op $op:tt $flatten:tt
         $(#$attr:tt)*
         $($move:ident)? |$input:tt : $input_ty:ty| -> $resume:ty $rest:block
     } => {{
         $crate::_try_transform_each! {
+            @op $op $flatten
             $(#$attr)* |$input : $input_ty, (mut _transform_sender): _| -> $resume {
                 $crate::_flow_local_macros! { ($) _transform_sender, $rest }
             }
         }
     }};
+    { @op $($x:tt)* } => {
+        ::core::compile_error!("`try_transform_each!` expects a closure taking an item and optionally a sender, like `|item| ...` or `|item, sender| ...`")
+    };
+    { $($rest:tt)* } => {
+        $crate::_try_transform_each! { @op [_flow_try_transform_op _ _ _ _ _] [] $($rest)* }
+    };
 }
 
+/// Like `transform_each!`, but the closure returns a `MyTry` and the first `Break` ends
+/// the flow. `#[flatten]` works as in [`try_for_each!`].
 #[macro_export]
 macro_rules! try_transform_each {
+    { #[flatten] $($x:tt)* } => {
+        $crate::normalize_op_input! { ($crate::_try_transform_each) { #[flatten] } $($x)* }
+    };
     { $($x:tt)* } => {
         $crate::normalize_op_input! { ($crate::_try_transform_each) {} $($x)* }
     };
@@ -167,6 +199,9 @@ macro_rules! _transform_each {
             }
         }
     }};
+    { $($x:tt)* } => {
+        ::core::compile_error!("`transform_each!` expects a closure taking an item and optionally a sender, like `|item| ...` or `|item, sender| ...`")
+    };
 }
 
 #[macro_export]
@@ -190,6 +225,9 @@ macro_rules! _map {
             }
         }
     };
+    { $($x:tt)* } => {
+        ::core::compile_error!("`map_each!` expects a closure taking one item, like `|item| ...`")
+    };
 }
 
 #[macro_export]
@@ -199,8 +237,59 @@ macro_rules! map_each {
     };
 }
 
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _map_concurrent {
+    {
+        ($op:ident, $limit:expr)
+        $(#[capture $cap:tt])*
+        $($move:ident)? |$input:tt : $input_ty:ty| -> $output_ty:ty $rest:block
+    } => {{
+        $crate::capture_outer! { [$($cap)*] }
+        // The futures outlive each call, so the closure always owns its captures and hands
+        // each future a fresh copy of them.
+        $crate::flows::ops::$op($limit, move |$input: $input_ty| {
+            $crate::capture_per_call! { [$($cap)*] }
+            async move {
+                $crate::capture_inner! { [$($cap)*] }
+                let out: $output_ty = async { $rest }.await;
+                out
+            }
+        })
+    }};
+}
+
+/// Like `map_each!`, but keeps up to `$limit` mapping futures in flight, yielding
+/// their outputs in source order.
+///
+/// Captures are re-run for each item's future: `#[capture(clone x)]` gives every future
+/// its own clone, and other captures are copied into it, so they must be `Copy` (for
+/// example `#[capture(ref x)]`). `ref mut` captures are rejected, since the futures run
+/// concurrently.
+#[macro_export]
+macro_rules! map_concurrent {
+    ($limit:expr, $($x:tt)*) => {
+        $crate::normalize_op_input! { ($crate::_map_concurrent) { (map_concurrent, $limit) } $($x)* }
+    };
+}
+
+/// Like [`map_concurrent!`], but yields outputs as soon as they complete.
+#[macro_export]
+macro_rules! map_concurrent_unordered {
+    ($limit:expr, $($x:tt)*) => {
+        $crate::normalize_op_input! {
+            ($crate::_map_concurrent) { (map_concurrent_unordered, $limit) } $($x)*
+        }
+    };
+}
+
 #[macro_export]
 macro_rules! merge_map {
+    (concurrency = $limit:expr, $($x:tt)*) => {
+        $crate::macro_utils::_flow_op(|src| $crate::flows::ops::merge_all_limited($limit).execute(
+            $crate::map_each!($($x)*).execute(src))
+        )
+    };
     ($($x:tt)*) => {
         $crate::macro_utils::_flow_op(|src| $crate::flows::ops::merge_all().execute(
             $crate::map_each!($($x)*).execute(src))
@@ -220,8 +309,17 @@ macro_rules! concat_map {
 #[macro_export]
 macro_rules! switch_map {
     ($($x:tt)*) => {
-        $crate::macro_utils::_flow_op(|src| $crate::flows::ops::flatten().execute(
-            $crate::map_each!($($x)*).execute($crate::flows::ops::latest().execute(src)))
+        $crate::macro_utils::_flow_op(|src| $crate::flows::ops::switch_all().execute(
+            $crate::map_each!($($x)*).execute(src))
+        )
+    };
+}
+
+#[macro_export]
+macro_rules! exhaust_map {
+    ($($x:tt)*) => {
+        $crate::macro_utils::_flow_op(|src| $crate::flows::ops::exhaust_all().execute(
+            $crate::map_each!($($x)*).execute(src))
         )
     };
 }
@@ -266,6 +364,9 @@ macro_rules! _filter {
             }
         }
     };
+    { $($x:tt)* } => {
+        ::core::compile_error!("`filter!` expects a closure taking a reference to an item, like `|item| ...`")
+    };
 }
 
 #[macro_export]
@@ -274,3 +375,32 @@ macro_rules! filter {
         $crate::normalize_op_input! { ($crate::_filter) {} $($x)* }
     };
 }
+
+/// Zips any number of flows (up to 12), yielding tuples and taking tuples as input.
+#[macro_export]
+macro_rules! zip {
+    ($($flow:expr),+ $(,)?) => {
+        $crate::flows::ops::Zip::new(($($crate::flows::convert::IntoFlow::into_flow($flow),)+))
+    };
+}
+
+/// Combines any number of flows (up to 12), yielding a tuple of their latest values
+/// whenever one of them yields. The flows are resumed with `()`.
+#[macro_export]
+macro_rules! combine_latest {
+    ($($flow:expr),+ $(,)?) => {
+        $crate::flows::ops::CombineLatest::new((
+            $($crate::flows::convert::IntoFlow::into_flow($flow),)+
+        ))
+    };
+}
+
+/// Merges any number of flows (up to 12) with the same `Yield`, yielding items as they arrive.
+#[macro_export]
+macro_rules! merge {
+    ($($flow:expr),+ $(,)?) => {
+        $crate::flows::ops::Merge::new((
+            $($crate::flows::convert::IntoFlow::into_flow($flow),)+
+        ))
+    };
+}
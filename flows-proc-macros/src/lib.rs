@@ -0,0 +1,420 @@
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{format_ident, quote, quote_spanned};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input, parse_quote, parse_quote_spanned,
+    punctuated::Punctuated,
+    spanned::Spanned,
+    Data, DeriveInput, Error, Fields, Ident, ItemFn, Pat, Path, ReturnType, Token, Type,
+};
+
+struct FlowArgs {
+    yield_ty: Type,
+    resume_ty: Type,
+    init: Pat,
+    krate: Path,
+}
+
+enum FlowArg {
+    Yield(Type),
+    Resume(Type),
+    Init(Pat),
+    Crate(Path),
+}
+
+impl Parse for FlowArg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let key = if input.peek(Token![yield]) {
+            let token: Token![yield] = input.parse()?;
+            Ident::new("yield", token.span)
+        } else if input.peek(Token![crate]) {
+            let token: Token![crate] = input.parse()?;
+            Ident::new("crate", token.span)
+        } else {
+            input.parse()?
+        };
+        input.parse::<Token![=]>()?;
+
+        Ok(match key.to_string().as_str() {
+            "yield" => FlowArg::Yield(input.parse()?),
+            "resume" => FlowArg::Resume(input.parse()?),
+            "init" => FlowArg::Init(Pat::parse_single(input)?),
+            "crate" => FlowArg::Crate(input.parse()?),
+            _ => {
+                return Err(Error::new(
+                    key.span(),
+                    "expected one of `yield`, `resume`, `init` or `crate`",
+                ))
+            }
+        })
+    }
+}
+
+impl Parse for FlowArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut yield_ty = None;
+        let mut resume_ty = None;
+        let mut init = None;
+        let mut krate = None;
+
+        for arg in Punctuated::<FlowArg, Token![,]>::parse_terminated(input)? {
+            match arg {
+                FlowArg::Yield(ty) => yield_ty = Some(ty),
+                FlowArg::Resume(ty) => resume_ty = Some(ty),
+                FlowArg::Init(pat) => init = Some(pat),
+                FlowArg::Crate(path) => krate = Some(path),
+            }
+        }
+
+        Ok(FlowArgs {
+            yield_ty: yield_ty
+                .ok_or_else(|| Error::new(Span::call_site(), "missing `yield = <type>`"))?,
+            resume_ty: resume_ty.unwrap_or_else(|| parse_quote!(())),
+            init: init.unwrap_or_else(|| parse_quote!(_)),
+            krate: krate.unwrap_or_else(|| parse_quote!(::flows)),
+        })
+    }
+}
+
+/// Turns an `async fn` into a function returning a flow.
+///
+/// ```ignore
+/// #[flow(yield = i32, resume = i32, init = first)]
+/// async fn running_total(start: i32) -> i32 {
+///     let mut total = start + first;
+///     for x in 1..=3 {
+///         total += next!(x);
+///     }
+///     total
+/// }
+/// ```
+///
+/// expands to `fn running_total(start: i32) -> impl Flow<i32, Yield = i32, Return = i32>`.
+/// `resume` defaults to `()`, and the first resume value, which starts the body, is bound
+/// to the `init` pattern (`_` by default). `next!`, `next_from!` and `sender!` work as in
+/// `flow!`. `crate` overrides the path to the `flows` crate.
+///
+/// Borrowed arguments need named lifetimes, which the returned flow captures.
+#[proc_macro_attribute]
+pub fn flow(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as FlowArgs);
+    let item = parse_macro_input!(item as ItemFn);
+
+    expand(args, item)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(args: FlowArgs, item: ItemFn) -> syn::Result<proc_macro2::TokenStream> {
+    let FlowArgs {
+        yield_ty,
+        resume_ty,
+        init,
+        krate,
+    } = args;
+    let ItemFn {
+        attrs,
+        vis,
+        mut sig,
+        block,
+    } = item;
+
+    let Some(asyncness) = sig.asyncness.take() else {
+        return Err(Error::new_spanned(
+            sig.fn_token,
+            "`#[flow]` can only be used on an `async fn`",
+        ));
+    };
+
+    let ret_ty: Type = match &sig.output {
+        ReturnType::Default => parse_quote!(()),
+        ReturnType::Type(_, ty) => (**ty).clone(),
+    };
+
+    let lifetimes = sig.generics.lifetimes().map(|param| &param.lifetime);
+    let captures = quote!(#(+ #lifetimes)*);
+
+    sig.output = parse_quote_spanned! {ret_ty.span()=>
+        -> impl #krate::__private::Flow<#resume_ty, Yield = #yield_ty, Return = #ret_ty> #captures
+    };
+
+    let body = quote_spanned! {block.span()=>
+        #krate::__private::_flow_local_macros! { ($) __flow_sender, #block }
+    };
+
+    Ok(quote! {
+        #(#attrs)*
+        #vis #sig {
+            #krate::__private::flow_from_async_fn(
+                #asyncness move |#init: #resume_ty, mut __flow_sender: #krate::__private::Sender<'_, #yield_ty, #resume_ty>| -> #ret_ty {
+                    #body
+                }
+            )
+        }
+    })
+}
+
+/// Implements `Flow` for an enum whose variants each hold a single flow, delegating to
+/// the held one. Every variant's flow must have the same `Yield` and `Return`, which
+/// the enum's flow inherits; `IntoFlow` then comes for free.
+///
+/// The held flows are structurally pinned: the enum is `Unpin` only when all of them
+/// are, and it can't implement `Drop`. `#[flow(crate = ...)]` overrides the path to the
+/// `flows` crate.
+#[proc_macro_derive(Flow, attributes(flow))]
+pub fn derive_flow(item: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(item as DeriveInput);
+
+    expand_derive_flow(item)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand_derive_flow(item: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let (variants, field_tys) = enum_variants(&item, "Flow")?;
+    let first_ty = field_tys[0];
+    let krate = crate_path(&item)?;
+
+    let name = &item.ident;
+    let (guard_generics, ty_generics, guard_where_clause) = item.generics.split_for_impl();
+
+    let mut unpin_generics = item.generics.clone();
+    unpin_generics.params.insert(0, parse_quote!('__pin));
+    unpin_generics
+        .make_where_clause()
+        .predicates
+        .push(parse_quote! {
+            (::core::marker::PhantomData<&'__pin ()>, #(#field_tys,)*): ::core::marker::Unpin
+        });
+    let (unpin_impl_generics, _, unpin_where_clause) = unpin_generics.split_for_impl();
+
+    let mut generics = item.generics.clone();
+    generics.params.push(parse_quote!(__FlowResume));
+    let predicates = &mut generics.make_where_clause().predicates;
+    predicates.push(parse_quote_spanned! {first_ty.span()=>
+        #first_ty: #krate::__private::Flow<__FlowResume>
+    });
+    for ty in &field_tys[1..] {
+        predicates.push(parse_quote_spanned! {ty.span()=>
+            #ty: #krate::__private::Flow<
+                __FlowResume,
+                Yield = <#first_ty as #krate::__private::Flow<__FlowResume>>::Yield,
+                Return = <#first_ty as #krate::__private::Flow<__FlowResume>>::Return,
+            >
+        });
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        // Pin guards: the enum is `Unpin` only if every held flow is (the `'__pin` lifetime
+        // keeps the bound from being trivial), and a `Drop` impl, which could move a held
+        // flow, conflicts with `MustNotImplDrop`.
+        const _: () = {
+            impl #unpin_impl_generics ::core::marker::Unpin for #name #ty_generics
+            #unpin_where_clause
+            {
+            }
+
+            trait MustNotImplDrop {}
+            #[allow(clippy::drop_bounds, drop_bounds)]
+            impl<T: ::core::ops::Drop> MustNotImplDrop for T {}
+            impl #guard_generics MustNotImplDrop for #name #ty_generics #guard_where_clause {}
+        };
+
+        impl #impl_generics #krate::__private::Flow<__FlowResume> for #name #ty_generics
+        #where_clause
+        {
+            type Yield = <#first_ty as #krate::__private::Flow<__FlowResume>>::Yield;
+            type Return = <#first_ty as #krate::__private::Flow<__FlowResume>>::Return;
+
+            fn poll_resume(
+                self: ::core::pin::Pin<&mut Self>,
+                cx: &mut ::core::task::Context,
+                input: &mut ::core::option::Option<__FlowResume>,
+            ) -> ::core::task::Poll<::core::ops::ControlFlow<Self::Return, Self::Yield>> {
+                // SAFETY: the pin guards above keep the held flow from being moved out of the
+                // pinned enum.
+                unsafe {
+                    match self.get_unchecked_mut() {
+                        #(Self::#variants(flow) => ::core::pin::Pin::new_unchecked(flow).poll_resume(cx, input),)*
+                    }
+                }
+            }
+
+            fn can_resume(self: ::core::pin::Pin<&mut Self>) -> bool {
+                // SAFETY: as above.
+                unsafe {
+                    match self.get_unchecked_mut() {
+                        #(Self::#variants(flow) => ::core::pin::Pin::new_unchecked(flow).can_resume(),)*
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Implements `IntoFlow` for an enum whose variants each hold a single value convertible
+/// into a flow, such as a `Vec`, a stream or another flow. Every variant's flow must have
+/// the same `Yield` and `Return`.
+///
+/// The flow is a generated enum named after this one with a `Flow` suffix, with the same
+/// variants and visibility, each holding the converted flow:
+///
+/// ```ignore
+/// #[derive(IntoFlow)]
+/// pub enum Source {
+///     Listed(Vec<i32>),
+///     Counted(Range<i32>),
+/// }
+/// ```
+///
+/// generates `pub enum SourceFlow<F0, F1> { Listed(F0), Counted(F1) }`, deriving `Flow`.
+/// `#[flow(crate = ...)]` overrides the path to the `flows` crate.
+#[proc_macro_derive(IntoFlow, attributes(flow))]
+pub fn derive_into_flow(item: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(item as DeriveInput);
+
+    expand_derive_into_flow(item)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand_derive_into_flow(item: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let (variants, field_tys) = enum_variants(&item, "IntoFlow")?;
+    let krate = crate_path(&item)?;
+
+    let name = &item.ident;
+    let vis = &item.vis;
+    let flow_name = format_ident!("{}Flow", name);
+    let flow_params: Vec<_> = (0..variants.len())
+        .map(|i| format_ident!("F{}", i))
+        .collect();
+    let markers: Vec<_> = (0..variants.len())
+        .map(|i| format_ident!("__FlowMarker{}", i))
+        .collect();
+    let flow_doc = format!("The flow of a [`{name}`], built by its `IntoFlow` impl.");
+
+    let first_ty = field_tys[0];
+    let first_marker = &markers[0];
+    let into_flow = |ty: &Type, marker: &Ident| {
+        quote_spanned! {ty.span()=>
+            <#ty as #krate::__private::IntoFlow<__FlowResume, #marker>>
+        }
+    };
+    let first = into_flow(first_ty, first_marker);
+
+    let (_, ty_generics, _) = item.generics.split_for_impl();
+    let mut generics = item.generics.clone();
+    generics.params.push(parse_quote!(__FlowResume));
+    for marker in &markers {
+        generics.params.push(parse_quote!(#marker));
+    }
+    let predicates = &mut generics.make_where_clause().predicates;
+    predicates.push(parse_quote_spanned! {first_ty.span()=>
+        #first_ty: #krate::__private::IntoFlow<__FlowResume, #first_marker>
+    });
+    for (ty, marker) in field_tys[1..].iter().zip(&markers[1..]) {
+        predicates.push(parse_quote_spanned! {ty.span()=>
+            #ty: #krate::__private::IntoFlow<
+                __FlowResume,
+                #marker,
+                Yield = #first::Yield,
+                Return = #first::Return,
+            >
+        });
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let flows = field_tys
+        .iter()
+        .zip(&markers)
+        .map(|(ty, marker)| into_flow(ty, marker));
+    let flows = quote!(#(#flows::IntoFlow),*);
+
+    Ok(quote! {
+        #[doc = #flow_doc]
+        #[derive(#krate::Flow)]
+        #[flow(crate = #krate)]
+        #vis enum #flow_name<#(#flow_params),*> {
+            #(#variants(#flow_params),)*
+        }
+
+        impl #impl_generics #krate::__private::IntoFlow<__FlowResume, (#(#markers,)*)>
+            for #name #ty_generics
+        #where_clause
+        {
+            type Yield = #first::Yield;
+            type Return = #first::Return;
+            type IntoFlow = #flow_name<#flows>;
+
+            fn into_flow(self) -> Self::IntoFlow {
+                match self {
+                    #(Self::#variants(value) => #flow_name::#variants(
+                        #krate::__private::IntoFlow::into_flow(value)
+                    ),)*
+                }
+            }
+        }
+    })
+}
+
+/// The variants of an enum deriving `derive`, each holding exactly one unnamed field, along
+/// with the fields' types.
+fn enum_variants<'a>(
+    item: &'a DeriveInput,
+    derive: &str,
+) -> syn::Result<(Vec<&'a Ident>, Vec<&'a Type>)> {
+    let Data::Enum(data) = &item.data else {
+        return Err(Error::new(
+            Span::call_site(),
+            format!("`#[derive({derive})]` only supports enums"),
+        ));
+    };
+
+    let mut variants = Vec::new();
+    let mut field_tys = Vec::new();
+    for variant in &data.variants {
+        let field = match &variant.fields {
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => &fields.unnamed[0],
+            _ => {
+                return Err(Error::new_spanned(
+                    variant,
+                    "each variant must hold exactly one flow, as in `Variant(MyFlow)`",
+                ))
+            }
+        };
+        variants.push(&variant.ident);
+        field_tys.push(&field.ty);
+    }
+
+    if variants.is_empty() {
+        return Err(Error::new(
+            Span::call_site(),
+            format!("`#[derive({derive})]` needs at least one variant"),
+        ));
+    }
+
+    Ok((variants, field_tys))
+}
+
+/// The path to the `flows` crate, from `#[flow(crate = ...)]` or `::flows` by default.
+fn crate_path(item: &DeriveInput) -> syn::Result<Path> {
+    let mut krate: Path = parse_quote!(::flows);
+    for attr in item
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("flow"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("crate") {
+                krate = meta.value()?.parse()?;
+                Ok(())
+            } else {
+                Err(meta.error("expected `crate`"))
+            }
+        })?;
+    }
+
+    Ok(krate)
+}
@@ -1,11 +1,19 @@
 use std::{
+    convert::Infallible,
+    future::Future,
+    iter,
     marker::PhantomData,
-    ops::ControlFlow::{self, Continue},
+    ops::ControlFlow::{self, Break, Continue},
     pin::Pin,
     task::{Context, Poll},
 };
 
-use flows_core::custom_fn::{CloneFn, MapFn, NewFn};
+use flows_core::{
+    convert::{IntoFlow, IterFlow},
+    custom_fn::{CloneFn, MapFn, NewFn},
+    Sender,
+};
+use flows_macros::flow;
 
 use super::Flow;
 
@@ -111,3 +119,103 @@ impl<Res, Ret> Flow<Res> for Identity<Ret> {
 pub fn identity<Ret>() -> Identity<Ret> {
     Identity { _ret: PhantomData }
 }
+
+pub struct Empty<Y> {
+    _yield: PhantomData<Y>,
+}
+
+impl<Y> Unpin for Empty<Y> {}
+
+impl<Res, Y> Flow<Res> for Empty<Y> {
+    type Yield = Y;
+    type Return = ();
+
+    fn poll_resume(
+        self: Pin<&mut Self>,
+        _: &mut Context,
+        input: &mut Option<Res>,
+    ) -> Poll<ControlFlow<Self::Return, Self::Yield>> {
+        input
+            .take()
+            .map(|_| Poll::Ready(Break(())))
+            .unwrap_or(Poll::Pending)
+    }
+}
+
+/// A flow that returns on its first resume without yielding anything.
+pub fn empty<Y>() -> Empty<Y> {
+    Empty {
+        _yield: PhantomData,
+    }
+}
+
+pub struct Pending<Y, Ret> {
+    _m: PhantomData<(Y, Ret)>,
+}
+
+impl<Y, Ret> Unpin for Pending<Y, Ret> {}
+
+impl<Res, Y, Ret> Flow<Res> for Pending<Y, Ret> {
+    type Yield = Y;
+    type Return = Ret;
+
+    fn poll_resume(
+        self: Pin<&mut Self>,
+        _: &mut Context,
+        _: &mut Option<Res>,
+    ) -> Poll<ControlFlow<Self::Return, Self::Yield>> {
+        Poll::Pending
+    }
+}
+
+/// A flow that neither yields nor returns.
+pub fn pending<Y, Ret>() -> Pending<Y, Ret> {
+    Pending { _m: PhantomData }
+}
+
+/// Like [`pending`], but with a type saying so: it can never yield or return.
+pub fn never() -> Pending<Infallible, Infallible> {
+    pending()
+}
+
+/// Yields the output of `fut`, then returns.
+pub fn once_future<'f, Fut>(fut: Fut) -> impl Flow<Yield = Fut::Output, Return = ()> + 'f
+where
+    Fut: 'f + Future,
+{
+    flow!(
+        #[capture(fut)]
+        |_: (), mut sender: Sender<Fut::Output, ()>| -> () {
+            sender.next(fut.await).await;
+        }
+    )
+}
+
+/// Yields items produced from a state by `fun` until it resolves to `None`.
+pub fn unfold<'f, S, Y, Fun, Fut>(init: S, fun: Fun) -> impl Flow<Yield = Y, Return = ()> + 'f
+where
+    S: 'f,
+    Y: 'f,
+    Fun: 'f + FnMut(S) -> Fut,
+    Fut: 'f + Future<Output = Option<(Y, S)>>,
+{
+    flow!(
+        #[capture(init, fun)]
+        |_: (), mut sender: Sender<Y, ()>| -> () {
+            let mut fun = fun;
+            let mut state = init;
+            while let Some((item, next)) = fun(state).await {
+                state = next;
+                sender.next(item).await;
+            }
+        }
+    )
+}
+
+/// Yields `first`, then each item computed from the previous one, until `succ` returns `None`.
+pub fn successors<T, Fun>(first: Option<T>, succ: Fun) -> IterFlow<iter::Successors<T, Fun>>
+where
+    Fun: FnMut(&T) -> Option<T>,
+{
+    iter::successors(first, succ).into_flow()
+}
@@ -0,0 +1,45 @@
+mod shared;
+mod state;
+
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+pub use shared::*;
+pub use state::*;
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[derive(Default)]
+struct SubscriberCount {
+    count: usize,
+    flow: Option<MutableStateFlow<usize>>,
+}
+
+impl SubscriberCount {
+    fn get(&self) -> usize {
+        self.count
+    }
+
+    fn set(&mut self, count: usize) {
+        self.count = count;
+        if let Some(flow) = &self.flow {
+            flow.set(count);
+        }
+    }
+
+    fn increment(&mut self) {
+        self.set(self.count + 1);
+    }
+
+    fn decrement(&mut self) {
+        self.set(self.count - 1);
+    }
+
+    fn as_state_flow(&mut self) -> StateFlow<usize> {
+        let count = self.count;
+        self.flow
+            .get_or_insert_with(|| MutableStateFlow::new(count))
+            .as_state_flow()
+    }
+}
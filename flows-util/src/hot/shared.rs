@@ -0,0 +1,400 @@
+use std::{
+    collections::VecDeque,
+    future::Future,
+    mem,
+    ops::ControlFlow::{self, Break, Continue},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
+
+use flows_core::Flow;
+
+use super::{lock, StateFlow, SubscriberCount};
+
+/// What to do when a value is emitted while the buffer of a shared flow is full.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BufferOverflow {
+    /// Suspend the emitter until every subscriber has made room.
+    #[default]
+    Suspend,
+    /// Drop the oldest buffered value, skipping it for subscribers that haven't seen it yet.
+    DropOldest,
+    /// Drop the value being emitted, keeping the buffer as is.
+    DropLatest,
+}
+
+struct SubscriberSlot {
+    index: u64,
+    waker: Option<Waker>,
+}
+
+struct SharedState<T> {
+    buffer: VecDeque<T>,
+    head: u64,
+    replay: usize,
+    capacity: usize,
+    overflow: BufferOverflow,
+    subscribers: Vec<Option<SubscriberSlot>>,
+    subscriber_count: SubscriberCount,
+    emitters: Vec<Waker>,
+    producers: usize,
+}
+
+impl<T> SharedState<T> {
+    fn tail(&self) -> u64 {
+        self.head + self.buffer.len() as u64
+    }
+
+    fn slots(&mut self) -> impl Iterator<Item = &mut SubscriberSlot> {
+        self.subscribers.iter_mut().flatten()
+    }
+
+    fn min_index(&self) -> u64 {
+        self.subscribers
+            .iter()
+            .flatten()
+            .map(|slot| slot.index)
+            .min()
+            .unwrap_or(self.tail())
+    }
+
+    fn trim(&mut self) {
+        let keep_from = self
+            .min_index()
+            .min(self.tail().saturating_sub(self.replay as u64))
+            .max(self.head);
+
+        while self.head < keep_from {
+            self.buffer.pop_front();
+            self.head += 1;
+        }
+    }
+
+    fn push(&mut self, value: T) {
+        self.buffer.push_back(value);
+        self.slots()
+            .filter_map(|slot| slot.waker.take())
+            .for_each(Waker::wake);
+    }
+
+    fn try_push(&mut self, value: T) -> Result<(), T> {
+        if self.subscriber_count.get() == 0 {
+            self.push(value);
+            self.trim();
+            return Ok(());
+        }
+
+        let pending = (self.tail() - self.min_index()) as usize;
+
+        if pending < self.capacity {
+            self.push(value);
+            return Ok(());
+        }
+
+        match self.overflow {
+            BufferOverflow::Suspend => Err(value),
+            BufferOverflow::DropLatest => Ok(()),
+            BufferOverflow::DropOldest => {
+                self.push(value);
+                let oldest = self.tail() - self.capacity as u64;
+                for slot in self.slots() {
+                    slot.index = slot.index.max(oldest);
+                }
+                self.trim();
+                Ok(())
+            }
+        }
+    }
+
+    fn register_emitter(&mut self, waker: &Waker) {
+        if !self.emitters.iter().any(|emitter| emitter.will_wake(waker)) {
+            self.emitters.push(waker.clone());
+        }
+    }
+
+    fn wake_emitters(&mut self) {
+        mem::take(&mut self.emitters)
+            .into_iter()
+            .for_each(Waker::wake);
+    }
+}
+
+type SharedInner<T> = Arc<Mutex<SharedState<T>>>;
+
+/// A read-only handle to a hot flow that broadcasts each emitted value to all of its subscribers.
+pub struct SharedFlow<T> {
+    inner: SharedInner<T>,
+}
+
+impl<T> Clone for SharedFlow<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> SharedFlow<T> {
+    /// Creates a new [`Subscription`] that first yields the current replay cache,
+    /// then every value emitted from now on.
+    pub fn subscribe(&self) -> Subscription<T> {
+        let mut state = lock(&self.inner);
+        let index = state
+            .tail()
+            .saturating_sub(state.replay as u64)
+            .max(state.head);
+        let slot = SubscriberSlot { index, waker: None };
+
+        let id = match state.subscribers.iter().position(Option::is_none) {
+            Some(id) => {
+                state.subscribers[id] = Some(slot);
+                id
+            }
+            None => {
+                state.subscribers.push(Some(slot));
+                state.subscribers.len() - 1
+            }
+        };
+        state.subscriber_count.increment();
+
+        Subscription {
+            inner: self.inner.clone(),
+            id,
+        }
+    }
+
+    /// The number of active subscriptions.
+    pub fn subscription_count(&self) -> usize {
+        lock(&self.inner).subscriber_count.get()
+    }
+
+    /// The number of active subscriptions, as a [`StateFlow`] that can be observed for changes.
+    pub fn subscription_count_flow(&self) -> StateFlow<usize> {
+        lock(&self.inner).subscriber_count.as_state_flow()
+    }
+
+    pub fn replay_cache(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        let state = lock(&self.inner);
+        let skip = state.buffer.len().saturating_sub(state.replay);
+        state.buffer.iter().skip(skip).cloned().collect()
+    }
+}
+
+/// The producing side of a [`SharedFlow`].
+///
+/// Subscriptions return once every `MutableSharedFlow` handle has been dropped
+/// and they've received all remaining buffered values.
+pub struct MutableSharedFlow<T> {
+    shared: SharedFlow<T>,
+}
+
+impl<T> MutableSharedFlow<T> {
+    /// Creates a shared flow that replays the last `replay` values to new subscribers
+    /// and buffers up to `replay + extra_buffer_capacity` values for slow subscribers.
+    ///
+    /// With a total capacity of zero the flow is a rendezvous: while there are subscribers,
+    /// [`emit`](Self::emit) waits until every one of them has taken the value, and
+    /// [`try_emit`](Self::try_emit) always hands the value back.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the total capacity is zero and `overflow` isn't [`BufferOverflow::Suspend`].
+    pub fn new(replay: usize, extra_buffer_capacity: usize, overflow: BufferOverflow) -> Self {
+        let capacity = replay + extra_buffer_capacity;
+        assert!(
+            capacity > 0 || overflow == BufferOverflow::Suspend,
+            "a shared flow without a buffer must use BufferOverflow::Suspend",
+        );
+
+        Self {
+            shared: SharedFlow {
+                inner: Arc::new(Mutex::new(SharedState {
+                    buffer: VecDeque::new(),
+                    head: 0,
+                    replay,
+                    capacity,
+                    overflow,
+                    subscribers: Vec::new(),
+                    subscriber_count: SubscriberCount::default(),
+                    emitters: Vec::new(),
+                    producers: 1,
+                })),
+            },
+        }
+    }
+
+    pub fn emit(&self, value: T) -> Emit<'_, T> {
+        Emit {
+            flow: self,
+            value: Some(value),
+            handed_off: None,
+        }
+    }
+
+    /// Emits `value` without suspending, handing it back if the buffer is full.
+    pub fn try_emit(&self, value: T) -> Result<(), T> {
+        lock(&self.shared.inner).try_push(value)
+    }
+
+    pub fn reset_replay_cache(&self) {
+        let mut state = lock(&self.shared.inner);
+        let replay = mem::take(&mut state.replay);
+        state.trim();
+        state.replay = replay;
+    }
+
+    pub fn as_shared_flow(&self) -> SharedFlow<T> {
+        self.shared.clone()
+    }
+
+    pub fn subscribe(&self) -> Subscription<T> {
+        self.shared.subscribe()
+    }
+
+    pub fn subscription_count(&self) -> usize {
+        self.shared.subscription_count()
+    }
+
+    pub fn subscription_count_flow(&self) -> StateFlow<usize> {
+        self.shared.subscription_count_flow()
+    }
+
+    pub fn replay_cache(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        self.shared.replay_cache()
+    }
+}
+
+impl<T> Default for MutableSharedFlow<T> {
+    fn default() -> Self {
+        Self::new(0, 0, BufferOverflow::Suspend)
+    }
+}
+
+impl<T> Clone for MutableSharedFlow<T> {
+    fn clone(&self) -> Self {
+        lock(&self.shared.inner).producers += 1;
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for MutableSharedFlow<T> {
+    fn drop(&mut self) {
+        let mut state = lock(&self.shared.inner);
+        state.producers -= 1;
+
+        if state.producers == 0 {
+            state
+                .slots()
+                .filter_map(|slot| slot.waker.take())
+                .for_each(Waker::wake);
+        }
+    }
+}
+
+pub struct Emit<'flow, T> {
+    flow: &'flow MutableSharedFlow<T>,
+    value: Option<T>,
+    /// Index of the value handed to the subscribers of an unbuffered flow, still waiting to be
+    /// taken by all of them.
+    handed_off: Option<u64>,
+}
+
+impl<T> Unpin for Emit<'_, T> {}
+
+impl<T> Future for Emit<'_, T> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let mut state = lock(&this.flow.shared.inner);
+
+        if let Some(index) = this.handed_off {
+            if state.min_index() > index {
+                this.handed_off = None;
+                return Poll::Ready(());
+            }
+            state.register_emitter(cx.waker());
+            return Poll::Pending;
+        }
+
+        let Some(value) = this.value.take() else {
+            return Poll::Ready(());
+        };
+
+        match state.try_push(value) {
+            Ok(()) => Poll::Ready(()),
+            Err(value) if state.capacity == 0 && state.tail() == state.min_index() => {
+                state.push(value);
+                this.handed_off = Some(state.tail() - 1);
+                state.register_emitter(cx.waker());
+                Poll::Pending
+            }
+            Err(value) => {
+                this.value = Some(value);
+                state.register_emitter(cx.waker());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// A subscriber to a [`SharedFlow`].
+pub struct Subscription<T> {
+    inner: SharedInner<T>,
+    id: usize,
+}
+
+impl<T> Unpin for Subscription<T> {}
+
+impl<T: Clone> Flow for Subscription<T> {
+    type Yield = T;
+    type Return = ();
+
+    fn poll_resume(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        input: &mut Option<()>,
+    ) -> Poll<ControlFlow<Self::Return, Self::Yield>> {
+        let mut state = lock(&self.inner);
+        let (head, tail, closed) = (state.head, state.tail(), state.producers == 0);
+        let slot = state.subscribers[self.id].as_mut().unwrap();
+
+        if input.is_none() {
+            slot.waker = Some(cx.waker().clone());
+            Poll::Pending
+        } else if slot.index < tail {
+            let index = slot.index;
+            slot.index += 1;
+            let value = state.buffer[(index - head) as usize].clone();
+            state.trim();
+            state.wake_emitters();
+            *input = None;
+            Poll::Ready(Continue(value))
+        } else if closed {
+            *input = None;
+            Poll::Ready(Break(()))
+        } else {
+            slot.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+impl<T> Drop for Subscription<T> {
+    fn drop(&mut self) {
+        let mut state = lock(&self.inner);
+        state.subscribers[self.id] = None;
+        state.subscriber_count.decrement();
+        state.trim();
+        state.wake_emitters();
+    }
+}
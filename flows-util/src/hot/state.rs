@@ -0,0 +1,290 @@
+use std::{
+    future::Future,
+    mem,
+    ops::ControlFlow::{self, Break, Continue},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{self, Context, Poll, Waker},
+};
+
+use flows_core::{
+    ops::{FlowOp, WrapOp},
+    Flow,
+};
+
+use super::{lock, SubscriberCount};
+
+struct StateState<T> {
+    value: T,
+    version: u64,
+    wakers: Vec<Waker>,
+    subscriber_count: SubscriberCount,
+    producers: usize,
+}
+
+impl<T> StateState<T> {
+    fn wake_all(&mut self) {
+        mem::take(&mut self.wakers)
+            .into_iter()
+            .for_each(Waker::wake);
+    }
+}
+
+type StateInner<T> = Arc<Mutex<StateState<T>>>;
+
+/// A read-only handle to a hot flow holding a single current value.
+pub struct StateFlow<T> {
+    inner: StateInner<T>,
+}
+
+impl<T> Clone for StateFlow<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> StateFlow<T> {
+    pub fn value(&self) -> T
+    where
+        T: Clone,
+    {
+        lock(&self.inner).value.clone()
+    }
+
+    /// Creates a [`StateSubscription`] that yields the current value, then the latest value
+    /// each time it changes.
+    pub fn subscribe(&self) -> StateSubscription<T> {
+        lock(&self.inner).subscriber_count.increment();
+        StateSubscription {
+            inner: self.inner.clone(),
+            version: None,
+        }
+    }
+
+    pub fn subscription_count(&self) -> usize {
+        lock(&self.inner).subscriber_count.get()
+    }
+
+    /// The number of active subscriptions, as a [`StateFlow`] that can be observed for changes.
+    pub fn subscription_count_flow(&self) -> StateFlow<usize> {
+        lock(&self.inner).subscriber_count.as_state_flow()
+    }
+}
+
+/// The producing side of a [`StateFlow`].
+///
+/// Subscriptions return once every `MutableStateFlow` handle has been dropped
+/// and they've received the final value.
+pub struct MutableStateFlow<T> {
+    state: StateFlow<T>,
+}
+
+impl<T> MutableStateFlow<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            state: StateFlow {
+                inner: Arc::new(Mutex::new(StateState {
+                    value,
+                    version: 0,
+                    wakers: Vec::new(),
+                    subscriber_count: SubscriberCount::default(),
+                    producers: 1,
+                })),
+            },
+        }
+    }
+
+    pub fn value(&self) -> T
+    where
+        T: Clone,
+    {
+        self.state.value()
+    }
+
+    /// Replaces the current value, notifying subscribers if it isn't equal to the old one.
+    pub fn set(&self, value: T)
+    where
+        T: PartialEq,
+    {
+        self.update(|_| value);
+    }
+
+    /// Computes a new value from the current one, notifying subscribers if it changed.
+    pub fn update(&self, f: impl FnOnce(&T) -> T)
+    where
+        T: PartialEq,
+    {
+        let mut state = lock(&self.state.inner);
+        let value = f(&state.value);
+
+        if value != state.value {
+            state.value = value;
+            state.version += 1;
+            state.wake_all();
+        }
+    }
+
+    pub fn as_state_flow(&self) -> StateFlow<T> {
+        self.state.clone()
+    }
+
+    pub fn subscribe(&self) -> StateSubscription<T> {
+        self.state.subscribe()
+    }
+
+    pub fn subscription_count(&self) -> usize {
+        self.state.subscription_count()
+    }
+
+    pub fn subscription_count_flow(&self) -> StateFlow<usize> {
+        self.state.subscription_count_flow()
+    }
+}
+
+impl<T: Default> Default for MutableStateFlow<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> Clone for MutableStateFlow<T> {
+    fn clone(&self) -> Self {
+        lock(&self.state.inner).producers += 1;
+        Self {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<T> Drop for MutableStateFlow<T> {
+    fn drop(&mut self) {
+        let mut state = lock(&self.state.inner);
+        state.producers -= 1;
+
+        if state.producers == 0 {
+            state.wake_all();
+        }
+    }
+}
+
+/// A subscriber to a [`StateFlow`].
+///
+/// Values are conflated, so a slow subscriber only sees the most recent one.
+pub struct StateSubscription<T> {
+    inner: StateInner<T>,
+    version: Option<u64>,
+}
+
+impl<T> Unpin for StateSubscription<T> {}
+
+impl<T: Clone> Flow for StateSubscription<T> {
+    type Yield = T;
+    type Return = ();
+
+    fn poll_resume(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        input: &mut Option<()>,
+    ) -> Poll<ControlFlow<Self::Return, Self::Yield>> {
+        let mut state = lock(&self.inner);
+
+        if input.is_none() {
+            if !state.wakers.iter().any(|w| w.will_wake(cx.waker())) {
+                state.wakers.push(cx.waker().clone());
+            }
+            Poll::Pending
+        } else if self.version != Some(state.version) {
+            let value = state.value.clone();
+            let version = state.version;
+            drop(state);
+            self.version = Some(version);
+            *input = None;
+            Poll::Ready(Continue(value))
+        } else if state.producers == 0 {
+            *input = None;
+            Poll::Ready(Break(()))
+        } else {
+            if !state.wakers.iter().any(|w| w.will_wake(cx.waker())) {
+                state.wakers.push(cx.waker().clone());
+            }
+            Poll::Pending
+        }
+    }
+}
+
+impl<T> Drop for StateSubscription<T> {
+    fn drop(&mut self) {
+        lock(&self.inner).subscriber_count.decrement();
+    }
+}
+
+pin_project_lite::pin_project!(
+    pub struct StateIn<Fl, T, Res> {
+        #[pin]
+        src: Fl,
+        state: Option<MutableStateFlow<T>>,
+        input: Option<Res>,
+    }
+);
+
+impl<Fl, T, Res> Future for StateIn<Fl, T, Res>
+where
+    Fl: Flow<Res, Yield = T>,
+    T: PartialEq,
+    Res: Default,
+{
+    type Output = Fl::Return;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        loop {
+            let this = self.as_mut().project();
+
+            match task::ready!(this.src.poll_resume(cx, this.input)) {
+                Continue(value) => {
+                    if let Some(state) = this.state {
+                        state.set(value);
+                    }
+                    this.input.get_or_insert_with(Res::default);
+                }
+                Break(ret) => {
+                    *this.state = None;
+                    return Poll::Ready(ret);
+                }
+            }
+        }
+    }
+}
+
+pub struct StateInOp<T> {
+    initial: T,
+}
+
+impl<Fl, T, Res> FlowOp<Fl, Res> for StateInOp<T>
+where
+    Fl: Flow<Res, Yield = T>,
+    Res: Default,
+{
+    type Output = (StateFlow<T>, StateIn<Fl, T, Res>);
+
+    fn execute(self, src: Fl) -> Self::Output {
+        let state = MutableStateFlow::new(self.initial);
+        (
+            state.as_state_flow(),
+            StateIn {
+                src,
+                state: Some(state),
+                input: Some(Res::default()),
+            },
+        )
+    }
+}
+
+/// Converts a cold flow into a [`StateFlow`] starting at `initial`.
+///
+/// Returns the state along with a [`StateIn`] future that drives the source,
+/// which should be spawned or polled alongside the state's subscribers.
+pub fn state_in<T>(initial: T) -> WrapOp<StateInOp<T>> {
+    WrapOp(StateInOp { initial })
+}
@@ -3,13 +3,48 @@ extern crate flows_macros;
 extern crate pin_project_lite;
 
 pub mod flow_impls;
+pub mod hot;
 pub mod ops;
 
-pub use convert::{FromFlow, IntoFlow};
-pub use flow_impls::{identity, on_each_sync, repeat, repeat_with};
-pub use flows_core::Flow;
-pub use flows_macros::{flow, flow_of};
+pub use convert::{FromFlow, IntoFlow, TryFromFlow};
+pub use flow_impls::{
+    empty, identity, never, on_each_sync, once_future, pending, repeat, repeat_with, successors,
+    unfold,
+};
+pub use flows_core::{
+    flow_from_async_fn,
+    io::{read_chunks, read_lines},
+    Flow, Sender,
+};
+pub use flows_macros::{callback_flow, channel_flow, flow, flow_of, one_of, select_flow, try_flow};
+pub use flows_proc_macros::{Flow, IntoFlow};
+
+pub mod channel {
+    pub use flows_core::channel::*;
+}
 
 pub mod convert {
     pub use flows_core::convert::*;
 }
+
+pub mod io {
+    pub use flows_core::io::*;
+}
+
+pub mod one_of {
+    pub use flows_core::one_of::*;
+}
+
+pub mod select {
+    pub use flows_core::select::*;
+}
+
+pub mod attr {
+    pub use flows_proc_macros::flow;
+}
+
+#[doc(hidden)]
+pub mod __private {
+    pub use flows_core::{convert::IntoFlow, flow_from_async_fn, Flow, Sender};
+    pub use flows_macros::_flow_local_macros;
+}
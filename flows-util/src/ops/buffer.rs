@@ -0,0 +1,124 @@
+use std::{
+    collections::VecDeque,
+    ops::ControlFlow::{self, Break, Continue},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use flows_core::{
+    ops::{FlowOp, WrapOp},
+    Flow,
+};
+
+pub use crate::hot::BufferOverflow;
+
+pin_project_lite::pin_project!(
+    /// Only takes `()` resumes: the upstream is resumed while the downstream is busy, so there
+    /// would be no downstream resume value to forward.
+    pub struct Buffer<Fl: Flow> {
+        #[pin]
+        inner: Fl,
+        buffer: VecDeque<Fl::Yield>,
+        capacity: usize,
+        overflow: BufferOverflow,
+        input: Option<()>,
+        ret: Option<Fl::Return>,
+    }
+);
+
+impl<Fl: Flow> Buffer<Fl> {
+    fn fill(self: Pin<&mut Self>, cx: &mut Context) {
+        let mut this = self.project();
+
+        while this.ret.is_none() {
+            let full = this.buffer.len() >= *this.capacity;
+            if full && *this.overflow == BufferOverflow::Suspend {
+                return;
+            }
+
+            if this.input.is_none() && this.inner.as_mut().can_resume() {
+                *this.input = Some(());
+            }
+
+            match this.inner.as_mut().poll_resume(cx, this.input) {
+                Poll::Ready(Continue(item)) => match this.overflow {
+                    _ if !full => this.buffer.push_back(item),
+                    BufferOverflow::DropOldest => {
+                        this.buffer.pop_front();
+                        this.buffer.push_back(item);
+                    }
+                    BufferOverflow::DropLatest => {}
+                    BufferOverflow::Suspend => unreachable!(),
+                },
+                Poll::Ready(Break(ret)) => *this.ret = Some(ret),
+                Poll::Pending => return,
+            }
+        }
+    }
+}
+
+impl<Fl: Flow> Flow for Buffer<Fl> {
+    type Yield = Fl::Yield;
+    type Return = Fl::Return;
+
+    fn poll_resume(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        input: &mut Option<()>,
+    ) -> Poll<ControlFlow<Self::Return, Self::Yield>> {
+        self.as_mut().fill(cx);
+
+        if input.is_none() {
+            return Poll::Pending;
+        }
+
+        let this = self.as_mut().project();
+
+        if let Some(item) = this.buffer.pop_front() {
+            *input = None;
+            // make room for the upstream to keep going while the downstream handles this item
+            self.fill(cx);
+            Poll::Ready(Continue(item))
+        } else if let Some(ret) = this.ret.take() {
+            *input = None;
+            Poll::Ready(Break(ret))
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// Lets the upstream run ahead of the downstream, buffering up to `capacity` items.
+///
+/// The upstream is resumed while the downstream is busy, and `overflow` decides what happens
+/// when the buffer is full.
+///
+/// # Panics
+///
+/// Panics if `capacity` is zero.
+pub fn buffer<'f, Fl>(
+    capacity: usize,
+    overflow: BufferOverflow,
+) -> WrapOp<impl FlowOp<Fl, (), Output = Buffer<Fl>> + 'f>
+where
+    Fl: 'f + Flow,
+{
+    assert!(capacity > 0, "buffer capacity must be at least 1");
+
+    WrapOp(move |inner| Buffer {
+        inner,
+        buffer: VecDeque::with_capacity(capacity),
+        capacity,
+        overflow,
+        input: None,
+        ret: None,
+    })
+}
+
+/// Keeps only the most recent item the downstream hasn't taken yet.
+pub fn conflate<'f, Fl>() -> WrapOp<impl FlowOp<Fl, (), Output = Buffer<Fl>> + 'f>
+where
+    Fl: 'f + Flow,
+{
+    buffer(1, BufferOverflow::DropOldest)
+}
@@ -1,3 +1,6 @@
+mod buffer;
+mod sequence;
+
 use core::task;
 use std::{
     marker::PhantomData,
@@ -6,143 +9,20 @@ use std::{
     task::{Context, Poll},
 };
 
+pub use buffer::*;
 pub use flows_core::ops::*;
-use flows_core::{convert::IntoFlow, custom_fn::MapFn, my_try::MyTry, Flow};
+use flows_core::{custom_fn::MapFn, my_try::MyTry, Flow};
+pub use sequence::*;
 
 pub use flows_macros::{
-    concat_map, filter, for_each, map_each, map_return, merge_map, switch_map, transform_each,
-    try_for_each, try_transform_each,
+    combine_latest, concat_map, exhaust_map, filter, for_each, map_concurrent,
+    map_concurrent_unordered, map_each, map_return, merge, merge_map, switch_map, transform_each,
+    try_for_each, try_transform_each, zip,
 };
 
 use crate::flow_impls::{on_each_sync, OnEachSync};
 
-pin_project_lite::pin_project!(
-    pub struct Zip<F0, F1, R0, R1, Y0, Y1> {
-        #[pin]
-        f0: F0,
-        #[pin]
-        f1: F1,
-        res: (Option<R0>, Option<R1>),
-        yld: (Option<Y0>, Option<Y1>),
-    }
-);
-
-impl<R0, R1, Y0, Y1, Ret, F0, F1> Flow<(R0, R1)> for Zip<F0, F1, R0, R1, Y0, Y1>
-where
-    F0: Flow<R0, Yield = Y0, Return = Ret>,
-    F1: Flow<R1, Yield = Y1, Return = Ret>,
-{
-    type Yield = (Y0, Y1);
-    type Return = Ret;
-
-    fn poll_resume(
-        self: Pin<&mut Self>,
-        cx: &mut Context,
-        input: &mut Option<(R0, R1)>,
-    ) -> Poll<ControlFlow<Self::Return, Self::Yield>> {
-        let mut this = self.project();
-
-        let mut inputs = match input.take() {
-            Some((v1, v2)) => {
-                *this.res = Default::default();
-                (Some(v1), Some(v2))
-            }
-            None => (None, None),
-        };
-
-        fn inner<Res, F>(
-            f: Pin<&mut F>,
-            input: &mut Option<Res>,
-            res: &mut Option<Res>,
-            yld: &mut Option<F::Yield>,
-            cx: &mut Context,
-        ) -> Poll<ControlFlow<F::Return>>
-        where
-            F: Flow<Res>,
-        {
-            f.poll_resume(cx, if input.is_some() { input } else { res })
-                .map(|ctrl| {
-                    MyTry::map(ctrl, |value| {
-                        *yld = Some(value);
-                    })
-                })
-        }
-
-        let polls = (
-            inner(
-                this.f0.as_mut(),
-                &mut inputs.0,
-                &mut this.res.0,
-                &mut this.yld.0,
-                cx,
-            ),
-            inner(
-                this.f1.as_mut(),
-                &mut inputs.1,
-                &mut this.res.1,
-                &mut this.yld.1,
-                cx,
-            ),
-        );
-
-        match inputs {
-            (Some(r0), Some(r1)) => {
-                *input = Some((r0, r1));
-            }
-            inputs => {
-                *this.res = inputs;
-            }
-        }
-
-        match polls {
-            (Poll::Ready(Break(ret)), _) | (_, Poll::Ready(Break(ret))) => {
-                return Poll::Ready(Break(ret))
-            }
-            (Poll::Pending, Poll::Pending) => return Poll::Pending,
-            _ => {
-                if let (Some(_), Some(_)) = this.yld {
-                    Poll::Ready(Continue((
-                        this.yld.0.take().unwrap(),
-                        this.yld.1.take().unwrap(),
-                    )))
-                } else {
-                    Poll::Pending
-                }
-            }
-        }
-    }
-
-    fn can_resume(self: Pin<&mut Self>) -> bool {
-        let this = self.project();
-        this.f0.can_resume() || this.f1.can_resume()
-    }
-}
-
-pub fn zip_with<Res0, Res1, Fl0, Fl1, M>(
-    rhs: impl IntoFlow<Res1, M, IntoFlow = Fl1>,
-) -> WrapOp<impl FlowOp<Fl0, Res0, Output = Zip<Fl0, Fl1, Res0, Res1, Fl0::Yield, Fl1::Yield>>>
-where
-    Fl0: Flow<Res0>,
-    Fl1: Flow<Res1>,
-{
-    WrapOp(|lhs| zip(lhs, rhs))
-}
-
-pub fn zip<Res0, Res1, Fl0, Fl1, M0, M1>(
-    f0: impl IntoFlow<Res0, M0, IntoFlow = Fl0>,
-    f1: impl IntoFlow<Res1, M1, IntoFlow = Fl1>,
-) -> Zip<Fl0, Fl1, Res0, Res1, Fl0::Yield, Fl1::Yield>
-where
-    Fl0: Flow<Res0>,
-    Fl1: Flow<Res1>,
-{
-    Zip {
-        f0: f0.into_flow(),
-        f1: f1.into_flow(),
-        res: Default::default(),
-        yld: Default::default(),
-    }
-}
+pub use crate::hot::state_in;
 
 pin_project_lite::pin_project!(
     pub struct DiscardInput<Fl, Res> {
@@ -194,6 +74,7 @@ where
     })
 }
 
+#[allow(clippy::type_complexity)]
 pub fn map_input<'f, A, B, F, Fl>(
     fun: F,
 ) -> WrapOp<impl FlowOp<Fl, B, Output = Compose<B, OnEachSync<F, Fl::Return>, Fl>> + 'f>
@@ -235,9 +116,9 @@ where
     }
 }
 
-pub fn try_unwrap<'f, Res, Fl: 'f>() -> WrapOp<impl FlowOp<Fl, Res, Output = TryUnwrap<Fl>> + 'f>
+pub fn try_unwrap<'f, Res, Fl>() -> WrapOp<impl FlowOp<Fl, Res, Output = TryUnwrap<Fl>> + 'f>
 where
-    Fl: Flow<Res>,
+    Fl: 'f + Flow<Res>,
 {
     WrapOp(|inner| TryUnwrap { inner })
 }
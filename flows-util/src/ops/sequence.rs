@@ -0,0 +1,133 @@
+use std::{
+    ops::ControlFlow::{self, Break, Continue},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use flows_core::{
+    ops::{FlowOp, WrapOp},
+    Flow, Sender,
+};
+use flows_macros::flow;
+
+/// Yields `values` before the source's first item.
+///
+/// The resume value after the last prepended item starts the source.
+pub fn start_with<'f, Res, Fl, I>(
+    values: I,
+) -> WrapOp<
+    impl FlowOp<Fl, Res, Output = impl Flow<Res, Yield = Fl::Yield, Return = Fl::Return> + 'f> + 'f,
+>
+where
+    Fl: 'f + Flow<Res>,
+    I: 'f + IntoIterator<Item = Fl::Yield>,
+    Res: 'f,
+{
+    WrapOp(move |src: Fl| {
+        flow!(
+            #[capture(src, values)]
+            |mut resume: Res, mut sender: Sender<Fl::Yield, Res>| -> Fl::Return {
+                for value in values {
+                    resume = sender.next(value).await;
+                }
+                sender.next_from(src, resume).await
+            }
+        )
+    })
+}
+
+/// Yields the values returned by `fun` after the source returns, then returns the
+/// source's `Return`.
+pub fn end_with<'f, Res, Fl, I, Fun>(
+    fun: Fun,
+) -> WrapOp<
+    impl FlowOp<Fl, Res, Output = impl Flow<Res, Yield = Fl::Yield, Return = Fl::Return> + 'f> + 'f,
+>
+where
+    Fl: 'f + Flow<Res>,
+    Fun: 'f + FnOnce(&Fl::Return) -> I,
+    I: IntoIterator<Item = Fl::Yield>,
+    Res: 'f,
+{
+    WrapOp(move |src: Fl| {
+        flow!(
+            #[capture(src, fun)]
+            |resume: Res, mut sender: Sender<Fl::Yield, Res>| -> Fl::Return {
+                let ret = sender.next_from(src, resume).await;
+                for value in fun(&ret) {
+                    sender.next(value).await;
+                }
+                ret
+            }
+        )
+    })
+}
+
+pin_project_lite::pin_project!(
+    pub struct Intersperse<Fl, Y> {
+        #[pin]
+        src: Fl,
+        separator: Y,
+        pending: Option<Y>,
+        started: bool,
+    }
+);
+
+impl<Fl, Y, Res> Flow<Res> for Intersperse<Fl, Y>
+where
+    Fl: Flow<Res, Yield = Y>,
+    Y: Clone,
+{
+    type Yield = Y;
+    type Return = Fl::Return;
+
+    fn poll_resume(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        input: &mut Option<Res>,
+    ) -> Poll<ControlFlow<Self::Return, Self::Yield>> {
+        let this = self.project();
+
+        // The resume value for a separator has nowhere to go, so it's dropped.
+        if this.pending.is_some() {
+            return match input.take() {
+                Some(_) => Poll::Ready(Continue(this.pending.take().unwrap())),
+                None => Poll::Pending,
+            };
+        }
+
+        match this.src.poll_resume(cx, input) {
+            Poll::Ready(Continue(item)) if *this.started => {
+                *this.pending = Some(item);
+                Poll::Ready(Continue(this.separator.clone()))
+            }
+            Poll::Ready(Continue(item)) => {
+                *this.started = true;
+                Poll::Ready(Continue(item))
+            }
+            Poll::Ready(Break(ret)) => Poll::Ready(Break(ret)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn can_resume(self: Pin<&mut Self>) -> bool {
+        let this = self.project();
+        this.pending.is_some() || this.src.can_resume()
+    }
+}
+
+/// Yields `separator` between consecutive items of the source.
+pub fn intersperse<'f, Res, Fl>(
+    separator: Fl::Yield,
+) -> WrapOp<impl FlowOp<Fl, Res, Output = Intersperse<Fl, Fl::Yield>> + 'f>
+where
+    Fl: 'f + Flow<Res>,
+    Fl::Yield: 'f + Clone,
+{
+    WrapOp(|src: Fl| Intersperse {
+        src,
+        separator,
+        pending: None,
+        started: false,
+    })
+}
@@ -0,0 +1,250 @@
+use std::{
+    future::{poll_fn, Future},
+    ops::ControlFlow::{Break, Continue},
+    pin::pin,
+    time::Duration,
+};
+
+use async_io::Timer;
+use flows_util::{
+    ops::{FlowOp, WrapOp},
+    Flow,
+};
+use futures_util::future::{select, Either};
+
+pub use flows_util::hot::*;
+
+/// Controls when the upstream of [`shared_from_fn`], [`share_in`] and friends starts and stops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SharingStarted {
+    /// Start the upstream immediately, even if nobody is subscribed.
+    Eagerly,
+    /// Start the upstream once the first subscriber appears and never stop it.
+    Lazily,
+    /// Start the upstream once the first subscriber appears, cancel it once there have been
+    /// no subscribers for `stop_timeout`, and start a new one when subscribers return.
+    ///
+    /// Only [`shared_from_fn`] and [`state_from_fn`] can build the new upstream.
+    WhileSubscribed { stop_timeout: Duration },
+}
+
+trait Emitter<T> {
+    fn emit_value(&self, value: T) -> impl Future<Output = ()> + '_;
+}
+
+impl<T> Emitter<T> for MutableSharedFlow<T> {
+    fn emit_value(&self, value: T) -> impl Future<Output = ()> + '_ {
+        self.emit(value)
+    }
+}
+
+impl<T: PartialEq> Emitter<T> for MutableStateFlow<T> {
+    async fn emit_value(&self, value: T) {
+        self.set(value)
+    }
+}
+
+async fn wait_for_count(
+    counts: &mut StateSubscription<usize>,
+    mut pred: impl FnMut(usize) -> bool,
+) -> bool {
+    loop {
+        match counts.next().await {
+            Continue(count) if pred(count) => return true,
+            Continue(_) => {}
+            Break(()) => return false,
+        }
+    }
+}
+
+async fn drive<Fl, Res, E>(src: Fl, emitter: &E) -> Fl::Return
+where
+    Fl: Flow<Res>,
+    Res: Default,
+    E: Emitter<Fl::Yield>,
+{
+    let mut src = pin!(src);
+    let mut input = Some(Res::default());
+    loop {
+        match poll_fn(|cx| src.as_mut().poll_resume(cx, &mut input)).await {
+            Continue(value) => {
+                emitter.emit_value(value).await;
+                input = Some(Res::default());
+            }
+            Break(ret) => return ret,
+        }
+    }
+}
+
+async fn share<Fl, Res, E>(
+    started: SharingStarted,
+    mut make: impl FnMut() -> Option<Fl>,
+    emitter: E,
+    counts: StateFlow<usize>,
+) -> Option<Fl::Return>
+where
+    Fl: Flow<Res>,
+    Res: Default,
+    E: Emitter<Fl::Yield>,
+{
+    let mut counts = counts.subscribe();
+    let mut src = make()?;
+
+    if started != SharingStarted::Eagerly && !wait_for_count(&mut counts, |n| n > 0).await {
+        return None;
+    }
+
+    let SharingStarted::WhileSubscribed { stop_timeout } = started else {
+        return Some(drive(src, &emitter).await);
+    };
+
+    loop {
+        let mut run = pin!(drive(src, &emitter));
+
+        loop {
+            let no_subscribers = wait_for_count(&mut counts, |n| n == 0);
+            match select(run.as_mut(), pin!(no_subscribers)).await {
+                Either::Left((ret, _)) => return Some(ret),
+                Either::Right((false, _)) => return None,
+                Either::Right((true, _)) => {}
+            }
+
+            let resubscribed = wait_for_count(&mut counts, |n| n > 0);
+            match select(
+                run.as_mut(),
+                select(Timer::after(stop_timeout), pin!(resubscribed)),
+            )
+            .await
+            {
+                Either::Left((ret, _)) => return Some(ret),
+                Either::Right((Either::Right((true, _)), _)) => {}
+                Either::Right((Either::Right((false, _)), _)) => return None,
+                Either::Right((Either::Left(_), _)) => break,
+            }
+        }
+
+        // The run was cancelled: start a fresh one once someone subscribes again.
+        src = make()?;
+        if !wait_for_count(&mut counts, |n| n > 0).await {
+            return None;
+        }
+    }
+}
+
+fn assert_restartable(started: SharingStarted, op: &str, instead: &str) {
+    assert!(
+        !matches!(started, SharingStarted::WhileSubscribed { .. }),
+        "`{op}` can't restart its source after WhileSubscribed cancels it; use `{instead}`",
+    );
+}
+
+/// Shares runs of the sources built by `make` between all subscribers of the returned
+/// [`SharedFlow`].
+///
+/// The returned future drives the current source according to `started`, and must be spawned
+/// or polled alongside the subscribers. When [`SharingStarted::WhileSubscribed`] cancels a run,
+/// a new source is built with `make` once there are subscribers again. The future resolves to
+/// the return value of the first run that completes, which closes the shared flow.
+#[allow(clippy::type_complexity)]
+pub fn shared_from_fn<'f, Res, Fl>(
+    mut make: impl 'f + FnMut() -> Fl,
+    started: SharingStarted,
+    replay: usize,
+) -> (
+    SharedFlow<Fl::Yield>,
+    impl Future<Output = Option<Fl::Return>> + 'f,
+)
+where
+    Fl: 'f + Flow<Res>,
+    Res: 'f + Default,
+{
+    let flow = MutableSharedFlow::new(replay, 0, BufferOverflow::Suspend);
+    let shared = flow.as_shared_flow();
+    let counts = flow.subscription_count_flow();
+    (shared, share(started, move || Some(make()), flow, counts))
+}
+
+/// Like [`shared_from_fn`], but keeps the latest value in a [`StateFlow`] that starts at
+/// `initial`.
+#[allow(clippy::type_complexity)]
+pub fn state_from_fn<'f, T, Res, Fl>(
+    mut make: impl 'f + FnMut() -> Fl,
+    started: SharingStarted,
+    initial: T,
+) -> (StateFlow<T>, impl Future<Output = Option<Fl::Return>> + 'f)
+where
+    Fl: 'f + Flow<Res, Yield = T>,
+    T: 'f + PartialEq,
+    Res: 'f + Default,
+{
+    let flow = MutableStateFlow::new(initial);
+    let state = flow.as_state_flow();
+    let counts = flow.subscription_count_flow();
+    (state, share(started, move || Some(make()), flow, counts))
+}
+
+/// Shares a single run of the source between all subscribers of the returned [`SharedFlow`].
+///
+/// The returned future drives the source according to `started`, and must be spawned or polled
+/// alongside the subscribers. It resolves to the source's return value, which closes the shared
+/// flow.
+///
+/// # Panics
+///
+/// Panics if `started` is [`SharingStarted::WhileSubscribed`]: a single flow can't be restarted
+/// once it's cancelled, so use [`shared_from_fn`] instead.
+#[allow(clippy::type_complexity)]
+pub fn share_in<'f, Res, Fl>(
+    started: SharingStarted,
+    replay: usize,
+) -> WrapOp<
+    impl FlowOp<
+            Fl,
+            Res,
+            Output = (
+                SharedFlow<Fl::Yield>,
+                impl Future<Output = Option<Fl::Return>> + 'f,
+            ),
+        > + 'f,
+>
+where
+    Fl: 'f + Flow<Res>,
+    Res: 'f + Default,
+{
+    assert_restartable(started, "share_in", "shared_from_fn");
+    WrapOp(move |src: Fl| {
+        let flow = MutableSharedFlow::new(replay, 0, BufferOverflow::Suspend);
+        let shared = flow.as_shared_flow();
+        let counts = flow.subscription_count_flow();
+        let mut src = Some(src);
+        (shared, share(started, move || src.take(), flow, counts))
+    })
+}
+
+/// Like [`state_in`](crate::ops::state_in), but starts the source according to `started`.
+///
+/// # Panics
+///
+/// Panics if `started` is [`SharingStarted::WhileSubscribed`], as with [`share_in`]; use
+/// [`state_from_fn`] instead.
+#[allow(clippy::type_complexity)]
+pub fn state_in_with<'f, T, Res, Fl>(
+    started: SharingStarted,
+    initial: T,
+) -> WrapOp<
+    impl FlowOp<Fl, Res, Output = (StateFlow<T>, impl Future<Output = Option<Fl::Return>> + 'f)> + 'f,
+>
+where
+    Fl: 'f + Flow<Res, Yield = T>,
+    T: 'f + PartialEq,
+    Res: 'f + Default,
+{
+    assert_restartable(started, "state_in_with", "state_from_fn");
+    WrapOp(move |src: Fl| {
+        let flow = MutableStateFlow::new(initial);
+        let state = flow.as_state_flow();
+        let counts = flow.subscription_count_flow();
+        let mut src = Some(src);
+        (state, share(started, move || src.take(), flow, counts))
+    })
+}
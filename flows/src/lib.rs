@@ -1,16 +1,39 @@
 extern crate async_io;
 extern crate flows_util;
+extern crate futures_util;
 
+pub mod hot;
 pub mod ops;
 
 pub use flows_util::{
-    flow, flow_of, identity, on_each_sync, repeat, repeat_with, Flow, FromFlow, IntoFlow,
+    callback_flow, channel_flow, empty, flow, flow_from_async_fn, flow_of, identity, never,
+    on_each_sync, once_future, one_of, pending, read_chunks, read_lines, repeat, repeat_with,
+    select_flow, successors, try_flow, unfold, Flow, FromFlow, IntoFlow, Sender, TryFromFlow,
 };
 
 pub mod flow_impls {
     pub use flows_util::flow_impls::*;
 }
 
+pub mod channel {
+    pub use flows_util::channel::*;
+}
+
 pub mod convert {
     pub use flows_util::convert::*;
 }
+
+pub mod io {
+    pub use flows_util::io::*;
+}
+
+pub mod select {
+    pub use flows_util::select::*;
+}
+
+pub mod attr {
+    pub use flows_util::attr::*;
+}
+
+#[doc(hidden)]
+pub use flows_util::__private;
@@ -4,13 +4,16 @@ use std::time::Duration;
 
 pub use flows_util::ops::*;
 
-pub fn delay_each<'f, Res: 'f, Fl: 'f>(
+pub use crate::hot::{share_in, state_in_with};
+
+pub fn delay_each<'f, Res, Fl>(
     duration: Duration,
 ) -> WrapOp<
     impl FlowOp<Fl, Res, Output = impl Flow<Res, Yield = Fl::Yield, Return = Fl::Return> + 'f> + 'f,
 >
 where
-    Fl: Flow<Res>,
+    Res: 'f,
+    Fl: 'f + Flow<Res>,
 {
     map_each!(
         #[capture(duration)]
@@ -1,18 +1,37 @@
 mod utils;
 
-use std::{convert::identity, time::Duration};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    convert::identity,
+    future::{self, IntoFuture},
+    io,
+    num::ParseIntError,
+    ops::ControlFlow::{Break, Continue},
+    pin::pin,
+    task::Poll,
+    time::Duration,
+};
 
 use either::Either;
 use flows::{
-    flow, flow_of,
+    attr::flow as flow_attr,
+    callback_flow, channel_flow,
+    convert::{try_collect_into, Bounded, CapacityError, ReceiverFlow, WriteInto},
+    empty, flow, flow_from_async_fn, flow_of, once_future, one_of,
     ops::{
-        concat_map, delay_each, filter, flatten, for_each, transform_each, try_for_each,
-        try_transform_each, zip,
+        all, any, buffer, chain, combine_latest, combine_latest_all, concat, concat_map, conflate,
+        count, delay_each, end_with, exhaust_map, filter, find, first, flatten, for_each,
+        for_each_async, intersperse, last, map_concurrent, map_concurrent_unordered,
+        map_each_async, max, merge, merge_map, min, nth, start_with, switch_map, transform_each,
+        try_for_each, try_transform_each, zip, zip_all, zip_longest, BufferOverflow, EitherOrBoth,
     },
-    Flow, FromFlow, IntoFlow,
+    pending, read_chunks, read_lines, repeat,
+    select::Selectable,
+    select_flow, successors, try_flow, unfold, Flow, FromFlow, IntoFlow, Sender,
 };
 
-use crate::utils::async_test;
+use crate::utils::{async_test, timeout};
 
 async fn sleep(ms: u64) {
     async_io::Timer::after(std::time::Duration::from_millis(ms)).await;
@@ -52,6 +71,26 @@ fn try_for_each_break() {
     })
 }
 
+#[test]
+fn flow_early_resume_keeps_last_item() {
+    async_test(async {
+        let mut f = std::pin::pin!(flow!(|| {
+            sleep(1).await;
+            next!(1);
+        }));
+
+        // Offers a resume on every poll, even before the flow has yielded.
+        let mut out = Vec::new();
+        while let Continue(x) =
+            std::future::poll_fn(|cx| f.as_mut().poll_resume(cx, &mut Some(()))).await
+        {
+            out.push(x);
+        }
+
+        assert_eq!(out, [1]);
+    })
+}
+
 #[test]
 fn flow_with_next_from() {
     async_test(async {
@@ -242,3 +281,1153 @@ fn flow_zip() {
         assert_eq!(out, [(1, "a"), (2, "b"), (3, "c")]);
     });
 }
+
+#[test]
+fn flow_channel_flow() {
+    async_test(async {
+        let f = channel_flow!(capacity = 2, |producer| {
+            let thread_producer = producer.clone();
+            let thread = std::thread::spawn(move || {
+                for i in 10..15 {
+                    futures::executor::block_on(thread_producer.send(i)).unwrap();
+                }
+            });
+
+            for i in 0..5 {
+                producer.send(i).await.unwrap();
+                sleep(1).await;
+            }
+            thread
+        });
+
+        let mut out = Vec::new();
+        let thread = f
+            .then(for_each!(
+                #[capture(ref mut out)]
+                |x| {
+                    out.push(x);
+                }
+            ))
+            .await;
+        thread.join().unwrap();
+
+        out.sort();
+        assert_eq!(out, [0, 1, 2, 3, 4, 10, 11, 12, 13, 14]);
+    });
+}
+
+#[test]
+fn flow_callback_flow() {
+    type Callback = Box<dyn Fn(i32) + Send>;
+
+    async_test(async {
+        let registry = std::sync::Mutex::new(None::<Callback>);
+        let registry = &registry;
+
+        let mut f = Box::pin(callback_flow!(move |producer| {
+            let producer = std::sync::Arc::new(producer);
+            let callback_producer = producer.clone();
+            *registry.lock().unwrap() = Some(Box::new(move |x| {
+                let _ = callback_producer.try_send(x);
+            }));
+
+            producer
+                .await_close(|| *registry.lock().unwrap() = None)
+                .await;
+        }));
+
+        let emit = |x| registry.lock().unwrap().as_ref().unwrap()(x);
+
+        let mut first = f.next().into_future();
+        assert!(futures::poll!(&mut first).is_pending());
+        emit(1);
+        emit(2);
+        assert_eq!(first.await, Continue(1));
+        assert_eq!(f.next().await, Continue(2));
+        assert!(registry.lock().unwrap().is_some());
+
+        drop(f);
+        assert!(registry.lock().unwrap().is_none());
+    });
+}
+
+#[test]
+fn flow_callback_flow_closes_on_return() {
+    async_test(async {
+        let kept = &RefCell::new(None);
+        let f = callback_flow!(move |producer| {
+            producer.send(1).await.unwrap();
+            *kept.borrow_mut() = Some(producer.clone());
+        });
+
+        assert_eq!(f.collect::<Vec<_>, _>().await, [1]);
+        assert_eq!(kept.borrow().as_ref().unwrap().try_send(2), Err(2));
+    });
+}
+
+#[test]
+fn flow_channel_flow_rendezvous() {
+    async_test(async {
+        let handle = &RefCell::new(None);
+        let mut f = pin!(channel_flow!(capacity = 0, move |producer| {
+            *handle.borrow_mut() = Some(producer);
+        }));
+
+        let mut next = f.next().into_future();
+        assert!(futures::poll!(&mut next).is_pending());
+        let producer = handle.borrow_mut().take().unwrap();
+        assert_eq!(producer.try_send(1), Ok(()));
+        assert_eq!(producer.try_send(2), Err(2));
+        assert_eq!(next.await, Continue(1));
+        assert_eq!(producer.try_send(3), Err(3));
+
+        drop(producer);
+        assert_eq!(f.next().await, Break(()));
+    });
+}
+
+#[test]
+fn flow_buffer() {
+    async_test(async {
+        let produced = &Cell::new(0);
+        let source = || {
+            flow!(move || {
+                for i in 0..5 {
+                    produced.set(produced.get() + 1);
+                    next!(i);
+                }
+            })
+        };
+        async fn seen(src: impl Flow<Yield = i32, Return = ()>, produced: &Cell<i32>) -> Vec<i32> {
+            let mut seen = Vec::new();
+            src.then(for_each!(
+                #[capture(ref mut seen)]
+                |_| {
+                    seen.push(produced.get());
+                    sleep(1).await;
+                }
+            ))
+            .await;
+            seen
+        }
+
+        assert_eq!(seen(source(), produced).await, [1, 2, 3, 4, 5]);
+
+        produced.set(0);
+        let buffered = source().then(buffer(2, BufferOverflow::Suspend));
+        assert_eq!(seen(buffered, produced).await, [3, 4, 5, 5, 5]);
+    });
+}
+
+#[test]
+fn flow_buffer_overflow() {
+    async_test(async {
+        let out = flow_of![1, 2, 3, 4, 5]
+            .then(buffer(2, BufferOverflow::DropLatest))
+            .then(Vec::from_flow)
+            .await;
+        assert_eq!(out, [1, 2]);
+
+        let out = flow_of![1, 2, 3, 4, 5]
+            .then(conflate())
+            .then(Vec::from_flow)
+            .await;
+        assert_eq!(out, [5]);
+    });
+}
+
+#[test]
+fn flow_merge_map() {
+    async_test(async {
+        let mut out = flow_of![1u64, 2, 3]
+            .then(merge_map!(|x| flow!(move || {
+                sleep(x * 5).await;
+                next!(x * 10);
+                sleep(20).await;
+                next!(x * 10 + 1);
+            })))
+            .then(Vec::from_flow)
+            .await;
+
+        assert_eq!(out[..3], [10, 20, 30]);
+        out.sort();
+        assert_eq!(out, [10, 11, 20, 21, 30, 31]);
+
+        // the main flow only stops once the other future gets a turn
+        let stop = &Cell::new(false);
+        let (out, ()) = futures::join!(
+            flow!(move || {
+                while !stop.get() {
+                    next!(());
+                }
+            })
+            .then(merge_map!(|_| empty::<u64>()))
+            .then(Vec::from_flow),
+            async { stop.set(true) },
+        );
+        assert!(out.is_empty());
+    });
+}
+
+#[test]
+fn flow_merge_map_forwards_input() {
+    async_test(async {
+        let replies = &RefCell::new(Vec::new());
+        flow!(|_: i32| {
+            next!(1);
+            next!(10);
+        })
+        .then(merge_map!(move |x| flow!(move |_: i32| {
+            let a = next!(x);
+            let b = next!(x + 1);
+            replies.borrow_mut().push((x, a, b));
+        })))
+        .then(for_each!(|x| -> i32 { x * 2 }))
+        .await;
+
+        let mut replies = replies.take();
+        replies.sort();
+        assert_eq!(replies, [(1, 2, 4), (10, 20, 22)]);
+    });
+}
+
+#[test]
+fn flow_merge_map_wake_while_item_waits() {
+    async_test(async {
+        // Each sub-flow wakes itself right before yielding, so the wake arrives while its
+        // item is still waiting to be taken.
+        let merged = flow_of![0, 10].then(merge_map!(|x| flow!(move || {
+            for i in 0..3 {
+                std::future::poll_fn(|cx| {
+                    cx.waker().wake_by_ref();
+                    Poll::Ready(())
+                })
+                .await;
+                next!(x + i);
+            }
+        })));
+        let out = timeout(1000, Vec::from_flow(merged)).await;
+
+        assert_eq!(out.len(), 6);
+    });
+}
+
+#[test]
+fn flow_merge_map_concurrency() {
+    async_test(async {
+        let active = &Cell::new(0);
+        let max_active = &Cell::new(0);
+
+        let mut out = flow_of![1, 2, 3, 4, 5, 6]
+            .then(merge_map!(concurrency = 2, move |x| flow!(move || {
+                active.set(active.get() + 1);
+                max_active.set(max_active.get().max(active.get()));
+                sleep(5).await;
+                next!(x);
+                active.set(active.get() - 1);
+            })))
+            .then(Vec::from_flow)
+            .await;
+
+        out.sort();
+        assert_eq!(out, [1, 2, 3, 4, 5, 6]);
+        assert_eq!(max_active.get(), 2);
+    });
+}
+
+#[test]
+fn flow_merge_map_fairness() {
+    async_test(async {
+        let out = flow_of![0, 1]
+            .then(merge_map!(|x| flow!(move || {
+                for i in 0..3 {
+                    next!((x, i));
+                }
+            })))
+            .then(Vec::from_flow)
+            .await;
+
+        let pos = |item| out.iter().position(|x| *x == item).unwrap();
+        assert_eq!(out.len(), 6);
+        assert!(pos((1, 0)) < pos((0, 2)));
+        assert!(pos((0, 2)) < pos((1, 2)));
+
+        let out = flow_of![(0..1000).collect::<Vec<u64>>()]
+            .then(flatten())
+            .then(merge_map!(|x| flow!(move || {
+                sleep(x % 7).await;
+                next!(x);
+            })))
+            .then(Vec::from_flow)
+            .await;
+
+        assert_eq!(out.len(), 1000);
+    });
+}
+
+#[test]
+fn flow_exhaust_map() {
+    async_test(async {
+        let clicks = flow!(|| {
+            for (x, delay) in [(1u64, 0), (2, 5), (3, 5), (4, 20)] {
+                sleep(delay).await;
+                next!(x);
+            }
+        });
+
+        let out = clicks
+            .then(exhaust_map!(|x| flow!(move || {
+                sleep(20).await;
+                next!(x);
+            })))
+            .then(Vec::from_flow)
+            .await;
+
+        assert_eq!(out, [1, 4]);
+
+        let (first_item, _) = repeat::<_, ()>(1)
+            .then(exhaust_map!(|x| flow!(move || {
+                next!(x);
+            })))
+            .then(first())
+            .await;
+        assert_eq!(first_item, Some(1));
+    });
+}
+
+#[test]
+fn flow_switch_map() {
+    struct Guard<'a>(u64, &'a RefCell<Vec<String>>);
+
+    impl Drop for Guard<'_> {
+        fn drop(&mut self) {
+            self.1.borrow_mut().push(format!("drop {}", self.0));
+        }
+    }
+
+    async_test(async {
+        let log = &RefCell::new(Vec::new());
+
+        let src = flow!(|| {
+            for (x, delay) in [(1u64, 0), (2, 10), (3, 80)] {
+                sleep(delay).await;
+                next!(x);
+            }
+        });
+
+        let out = src
+            .then(switch_map!(move |x| flow!(move || {
+                log.borrow_mut().push(format!("start {x}"));
+                let _guard = Guard(x, log);
+                sleep(20).await;
+                next!(x * 10);
+                sleep(20).await;
+                next!(x * 10 + 1);
+            })))
+            .then(Vec::from_flow)
+            .await;
+
+        assert_eq!(out, [20, 21, 30, 31]);
+        assert_eq!(
+            *log.borrow(),
+            ["start 1", "drop 1", "start 2", "drop 2", "start 3", "drop 3"]
+        );
+
+        let (first_item, _) = repeat::<_, ()>(1)
+            .then(switch_map!(|x| flow!(move || {
+                next!(x);
+            })))
+            .then(first())
+            .await;
+        assert_eq!(first_item, Some(1));
+    });
+}
+
+#[test]
+fn flow_map_concurrent() {
+    async_test(async {
+        let active = &Cell::new(0);
+        let max_active = &Cell::new(0);
+
+        let out = flow_of![30u64, 10, 20, 5]
+            .then(map_concurrent!(2, move |x| {
+                active.set(active.get() + 1);
+                max_active.set(max_active.get().max(active.get()));
+                sleep(x).await;
+                active.set(active.get() - 1);
+                x
+            }))
+            .then(Vec::from_flow)
+            .await;
+
+        assert_eq!(out, [30, 10, 20, 5]);
+        assert_eq!(max_active.get(), 2);
+
+        let offset = 1;
+        let out = flow_of![30u64, 10, 20]
+            .then(map_concurrent_unordered!(
+                3,
+                #[capture(ref offset)]
+                |x| {
+                    sleep(x).await;
+                    x + offset
+                }
+            ))
+            .then(Vec::from_flow)
+            .await;
+
+        assert_eq!(out, [11, 21, 31]);
+
+        struct Counted<'a>(&'a Cell<u32>);
+        impl Clone for Counted<'_> {
+            fn clone(&self) -> Self {
+                self.0.set(self.0.get() + 1);
+                Counted(self.0)
+            }
+        }
+
+        let clones = Cell::new(0);
+        let counted = Counted(&clones);
+        let out = flow_of![1, 2, 3]
+            .then(map_concurrent!(
+                3,
+                #[capture(clone counted)]
+                |x| {
+                    let counted = counted;
+                    sleep(1).await;
+                    x + counted.0.get()
+                }
+            ))
+            .then(Vec::from_flow)
+            .await;
+
+        // one clone for the op, then one for each item's future
+        assert_eq!(clones.get(), 4);
+        assert_eq!(out, [5, 6, 7]);
+
+        // the futures run even while the downstream isn't asking for their outputs
+        let ran = &Cell::new(0);
+        let mut f = pin!(flow_of![1, 2].then(map_concurrent!(2, move |x| {
+            ran.set(ran.get() + 1);
+            x
+        })));
+        let polled = future::poll_fn(|cx| Poll::Ready(f.as_mut().poll_resume(cx, &mut None))).await;
+        assert!(polled.is_pending());
+        assert_eq!(ran.get(), 2);
+        assert_eq!(f.next().await, Continue(1));
+        assert_eq!(f.next().await, Continue(2));
+        assert_eq!(f.next().await, Break(()));
+    });
+}
+
+#[test]
+fn flow_zip_n() {
+    async_test(async {
+        let out = zip!(
+            flow_of![1, 2, 3].then(delay_each(Duration::from_millis(3))),
+            flow_of!["a", "b", "c", "d"],
+            flow_of![true, false, true, false].then(delay_each(Duration::from_millis(5))),
+        )
+        .then(Vec::from_flow)
+        .await;
+
+        assert_eq!(out, [(1, "a", true), (2, "b", false), (3, "c", true)]);
+
+        let out = zip_all((0..3).map(|i| [i, i * 10]))
+            .then(Vec::from_flow)
+            .await;
+
+        assert_eq!(out, [vec![0, 1, 2], vec![0, 10, 20]]);
+
+        let out = zip_longest(flow_of![1, 2, 3], flow_of!["a"])
+            .then(Vec::from_flow)
+            .await;
+
+        assert_eq!(
+            out,
+            [
+                EitherOrBoth::Both(1, "a"),
+                EitherOrBoth::Left(2),
+                EitherOrBoth::Left(3)
+            ]
+        );
+    });
+}
+
+#[test]
+fn flow_combine_latest() {
+    async_test(async {
+        let timed = |items: Vec<(u64, i32)>| {
+            flow!(move || {
+                for (delay, x) in items {
+                    sleep(delay).await;
+                    next!(x);
+                }
+            })
+        };
+
+        let out = combine_latest(
+            timed(vec![(0, 1), (20, 2)]),
+            timed(vec![(10, 10), (20, 20)]),
+        )
+        .then(Vec::from_flow)
+        .await;
+
+        assert_eq!(out, [(1, 10), (2, 10), (2, 20)]);
+
+        let out = combine_latest!(flow_of![1], timed(vec![(0, 10), (10, 20)]), flow_of!["a"])
+            .then(Vec::from_flow)
+            .await;
+
+        assert_eq!(out, [(1, 10, "a"), (1, 20, "a")]);
+
+        let out = combine_latest_all([timed(vec![(0, 1), (20, 2)]), timed(vec![(10, 10)])])
+            .then(Vec::from_flow)
+            .await;
+
+        assert_eq!(out, [vec![1, 10], vec![2, 10]]);
+    });
+}
+
+#[test]
+fn flow_merge_forwards_input() {
+    async_test(async {
+        let replies = &RefCell::new(Vec::new());
+        let replying = |x: i32| {
+            flow!(move |_: i32| {
+                let a = next!(x);
+                let b = next!(x + 1);
+                replies.borrow_mut().push((x, a, b));
+            })
+        };
+
+        merge(replying(1), replying(10))
+            .then(for_each!(|x| -> i32 { x * 2 }))
+            .await;
+
+        let mut replies = replies.take();
+        replies.sort();
+        assert_eq!(replies, [(1, 2, 4), (10, 20, 22)]);
+    });
+}
+
+#[test]
+fn flow_merge_concat() {
+    async_test(async {
+        let timed = |items: Vec<(u64, i32)>, ret: &'static str| {
+            flow!(move || {
+                for (delay, x) in items {
+                    sleep(delay).await;
+                    next!(x);
+                }
+                ret
+            })
+        };
+
+        let mut out = Vec::new();
+        let ret = merge(timed(vec![(0, 1), (20, 3)], "a"), timed(vec![(10, 2)], "b"))
+            .then(for_each!(
+                #[capture(ref mut out)]
+                |x| {
+                    out.push(x);
+                }
+            ))
+            .await;
+
+        assert_eq!(out, [1, 2, 3]);
+        assert_eq!(ret, ("a", "b"));
+
+        let mut out = merge!(flow_of![1, 2], flow_of![10, 20], flow_of![100])
+            .then(Vec::from_flow)
+            .await;
+        assert_eq!(out[..3], [1, 10, 100]);
+        out.sort();
+        assert_eq!(out, [1, 2, 10, 20, 100]);
+
+        let out = (flow_of![1], flow_of![2])
+            .into_flow()
+            .then(Vec::from_flow)
+            .await;
+        assert_eq!(out, [1, 2]);
+
+        let mut out = Vec::new();
+        let ret = concat(timed(vec![(10, 1), (0, 2)], "a"), timed(vec![(0, 3)], "b"))
+            .then(for_each!(
+                #[capture(ref mut out)]
+                |x| {
+                    out.push(x);
+                }
+            ))
+            .await;
+
+        assert_eq!(out, [1, 2, 3]);
+        assert_eq!(ret, ("a", "b"));
+
+        let out = flow_of![1, 2]
+            .then(chain(flow_of![3]))
+            .then(Vec::from_flow)
+            .await;
+        assert_eq!(out, [1, 2, 3]);
+    });
+}
+
+#[test]
+fn flow_start_end_intersperse() {
+    async_test(async {
+        let src = flow!(|| {
+            next!(1);
+            next!(2);
+            "done"
+        });
+
+        let mut out = Vec::new();
+        let ret = src
+            .then(start_with([-1, 0]))
+            .then(end_with(|ret: &&str| [ret.len() as i32]))
+            .then(intersperse(100))
+            .then(for_each!(
+                #[capture(ref mut out)]
+                |x| {
+                    out.push(x);
+                }
+            ))
+            .await;
+
+        assert_eq!(out, [-1, 100, 0, 100, 1, 100, 2, 100, 4]);
+        assert_eq!(ret, "done");
+
+        let out = flow_of![1].then(intersperse(0)).then(Vec::from_flow).await;
+        assert_eq!(out, [1]);
+    });
+}
+
+#[test]
+fn flow_query_ops() {
+    async_test(async {
+        let produced = Cell::new(0);
+        let src = || {
+            flow!(
+                #[capture(ref produced)]
+                || {
+                    for x in [3, 1, 4, 1, 5] {
+                        produced.set(produced.get() + 1);
+                        next!(x);
+                    }
+                    "done"
+                }
+            )
+        };
+
+        assert_eq!(src().then(first()).await, (Some(3), None));
+        assert_eq!(produced.replace(0), 1);
+
+        assert_eq!(src().then(nth(2)).await, (Some(4), None));
+        assert_eq!(src().then(nth(5)).await, (None, Some("done")));
+        assert_eq!(src().then(find(|x| *x > 3)).await, (Some(4), None));
+        assert_eq!(produced.replace(0), 3 + 5 + 3);
+
+        assert_eq!(src().then(any(|x| *x == 1)).await, (true, None));
+        assert_eq!(src().then(any(|x| *x == 2)).await, (false, Some("done")));
+        assert_eq!(src().then(all(|x| *x < 5)).await, (false, None));
+        assert_eq!(src().then(all(|x| *x > 0)).await, (true, Some("done")));
+
+        assert_eq!(src().then(last()).await, (Some(5), "done"));
+        assert_eq!(src().then(count()).await, (5, "done"));
+        assert_eq!(src().then(min()).await, (Some(1), "done"));
+        assert_eq!(src().then(max()).await, (Some(5), "done"));
+        assert_eq!(flow_of![].then(max::<_, _>()).await, (None::<i32>, ()));
+    });
+}
+
+#[test]
+fn flow_collect() {
+    async_test(async {
+        let src = || flow_of![(1, 'a'), (2, 'b'), (3, 'c')];
+
+        let map = src().collect::<HashMap<_, _>, _>().await;
+        assert_eq!(map[&2], 'b');
+
+        let (nums, chars) = src().collect::<(Vec<_>, String), _>().await;
+        assert_eq!(nums, [1, 2, 3]);
+        assert_eq!(chars, "abc");
+
+        let produced = Cell::new(0);
+        let src = || {
+            flow!(
+                #[capture(ref produced)]
+                || {
+                    for x in [Ok(1), Err("two"), Ok(3)] {
+                        produced.set(produced.get() + 1);
+                        next!(x);
+                    }
+                    "done"
+                }
+            )
+        };
+
+        let collected = src().collect::<Result<Vec<i32>, _>, _>().await;
+        assert_eq!(collected, Err("two"));
+        assert_eq!(produced.get(), 2);
+
+        let (collected, ret) = src().collect_with_return::<Result<Vec<i32>, _>, _>().await;
+        assert_eq!((collected, ret), (Err("two"), None));
+
+        let ok = flow_of![Ok::<_, ()>(1), Ok(2)].collect::<Result<Vec<_>, _>, _>();
+        assert_eq!(ok.await, Ok(vec![1, 2]));
+
+        let some = flow_of![Some(1), None, Some(3)].collect::<Option<Vec<_>>, _>();
+        assert_eq!(some.await, None);
+
+        let (collected, ret) = flow!(|| {
+            next!(1);
+            next!(2);
+            "done"
+        })
+        .collect_with_return::<Vec<_>, _>()
+        .await;
+        assert_eq!((collected, ret), (vec![1, 2], Some("done")));
+    });
+}
+
+#[test]
+fn flow_try_collect() {
+    async_test(async {
+        let bounded = flow_of![1, 2].try_collect::<Bounded<Vec<_>, 2>, _>().await;
+        assert_eq!(bounded.unwrap().into_inner(), [1, 2]);
+
+        let produced = Cell::new(0);
+        let full = flow!(
+            #[capture(ref produced)]
+            || {
+                for x in 1..=5 {
+                    produced.set(produced.get() + 1);
+                    next!(x);
+                }
+            }
+        )
+        .try_collect::<Bounded<Vec<_>, 2>, _>()
+        .await;
+        assert_eq!(full, Err(CapacityError(3)));
+        assert_eq!(produced.get(), 3);
+
+        let written = flow_of!["ab", "cd"]
+            .try_collect::<WriteInto<io::Cursor<Vec<u8>>>, _>()
+            .await;
+        assert_eq!(written.unwrap().into_inner().into_inner(), b"abcd");
+
+        let mut buf = [0; 3];
+        let err = try_collect_into(flow_of!["ab", "cd"], WriteInto(&mut buf[..])).await;
+        assert_eq!(err.unwrap_err().kind(), io::ErrorKind::WriteZero);
+        assert_eq!(&buf, b"abc");
+
+        let failed = flow_of![Ok(1), Err("bad"), Ok(3)]
+            .try_collect::<Result<Bounded<Vec<_>, 2>, _>, _>()
+            .await;
+        assert_eq!(failed.unwrap(), Err("bad"));
+
+        let full = flow_of![Some(1), Some(2), Some(3)]
+            .try_collect::<Option<Bounded<Vec<_>, 2>>, _>()
+            .await;
+        assert_eq!(full, Err(CapacityError(3)));
+
+        let ok = flow_of![Some(1), Some(2)]
+            .try_collect::<Option<Bounded<Vec<_>, 2>>, _>()
+            .await;
+        assert_eq!(ok.unwrap().unwrap().into_inner(), [1, 2]);
+    });
+}
+
+#[test]
+fn flow_sources() {
+    async_test(async {
+        let out = once_future(async {
+            sleep(5).await;
+            1
+        })
+        .collect::<Vec<_>, _>()
+        .await;
+        assert_eq!(out, [1]);
+
+        assert_eq!(Flow::<()>::collect::<Vec<_>, _>(empty::<i32>()).await, []);
+
+        let (out, ret) = merge(flow_of![1, 2], pending::<_, ()>())
+            .then(first())
+            .await;
+        assert_eq!((out, ret), (Some(1), None));
+
+        let out = unfold(0, |n| async move { (n < 3).then_some((n * 10, n + 1)) })
+            .collect::<Vec<_>, _>()
+            .await;
+        assert_eq!(out, [0, 10, 20]);
+
+        let out = successors(Some(1), |n| (*n < 100).then_some(n * 10))
+            .collect::<Vec<_>, _>()
+            .await;
+        assert_eq!(out, [1, 10, 100]);
+
+        let out = (0..10).step_by(4).into_flow().collect::<Vec<_>, _>().await;
+        assert_eq!(out, [0, 4, 8]);
+
+        let (tx, rx) = std::sync::mpsc::sync_channel::<i32>(0);
+        let sender = std::thread::spawn(move || {
+            for x in 1..=3 {
+                tx.send(x).unwrap();
+            }
+        });
+        let out = ReceiverFlow::new(rx).collect::<Vec<_>, _>().await;
+        assert_eq!(out, [1, 2, 3]);
+        sender.join().unwrap();
+
+        let (mut tx, rx) = futures::channel::mpsc::channel::<i32>(0);
+        let (out, ()) = futures::join!(rx.into_flow().collect::<Vec<_>, _>(), async move {
+            for x in 1..=3 {
+                sleep(1).await;
+                futures::SinkExt::send(&mut tx, x).await.unwrap();
+            }
+        });
+        assert_eq!(out, [1, 2, 3]);
+
+        // `into_flow()` alone also matches the blocking `IntoIterator`, so pick the marker.
+        let (tx, rx) = std::sync::mpsc::channel::<i32>();
+        let rx = IntoFlow::<(), ReceiverFlow<()>>::into_flow(rx);
+        let (out, ()) = futures::join!(rx.collect::<Vec<_>, _>(), async move {
+            for x in 1..=3 {
+                sleep(1).await;
+                tx.send(x).unwrap();
+            }
+        });
+        assert_eq!(out, [1, 2, 3]);
+    });
+}
+
+#[test]
+fn flow_async_closures() {
+    async_test(async {
+        let src = flow_from_async_fn(async |mut total: i32, mut sender: Sender<'_, i32, i32>| {
+            for x in 1..=3 {
+                total += sender.next(x).await;
+            }
+            total
+        });
+
+        let mut seen = Vec::new();
+        let ret = src
+            .then(map_each_async(async |x| {
+                sleep(1).await;
+                x * 10
+            }))
+            .then(for_each_async(async |x| {
+                seen.push(x);
+                x
+            }))
+            .await;
+
+        assert_eq!(seen, [10, 20, 30]);
+        assert_eq!(ret, 60);
+    });
+}
+
+#[flow_attr(yield = i32, resume = i32, init = first)]
+async fn running_total(start: i32) -> i32 {
+    let mut total = start + first;
+    for x in 1..=3 {
+        total += next!(x);
+    }
+    total
+}
+
+#[flow_attr(yield = &'a str)]
+async fn words<'a>(text: &'a str) -> usize {
+    let mut count = 0;
+    for word in text.split_whitespace() {
+        next!(word);
+        count += 1;
+    }
+    next_from!(flow_of!["!"]);
+    count
+}
+
+#[test]
+fn flow_attribute() {
+    async_test(async {
+        let mut out = Vec::new();
+        let ret = running_total(100)
+            .then(for_each!(
+                #[capture(ref mut out)]
+                |x| -> i32 {
+                    out.push(x);
+                    x * 2
+                }
+            ))
+            .await;
+        assert_eq!(out, [1, 2, 3]);
+        assert_eq!(ret, 100 + 12);
+
+        let text = String::from("hello flow world");
+        let (out, ret) = words(&text).collect_with_return::<Vec<_>, _>().await;
+        assert_eq!(out, ["hello", "flow", "world", "!"]);
+        assert_eq!(ret, Some(3));
+    });
+}
+
+#[test]
+fn flow_try_flow() {
+    fn parse(
+        input: &'static [&'static str],
+    ) -> impl Flow<Yield = i32, Return = Result<usize, ParseIntError>> {
+        try_flow!(move || -> Result<usize, ParseIntError> {
+            for s in input {
+                next!(s.parse::<i32>()?);
+            }
+            input.len()
+        })
+    }
+
+    async_test(async {
+        let (out, ret) = parse(&["1", "2"]).collect_with_return::<Vec<_>, _>().await;
+        assert_eq!(out, [1, 2]);
+        assert_eq!(ret, Some(Ok(2)));
+
+        let (out, ret) = parse(&["1", "x", "3"])
+            .collect_with_return::<Vec<_>, _>()
+            .await;
+        assert_eq!(out, [1]);
+        assert!(ret.unwrap().is_err());
+
+        let outer = try_flow!(|| -> Result<usize, ParseIntError> {
+            let parsed = next_from!(parse(&["1", "x"]))?;
+            next!(100);
+            parsed
+        });
+        let (out, ret) = outer.collect_with_return::<Vec<_>, _>().await;
+        assert_eq!(out, [1]);
+        assert!(ret.unwrap().is_err());
+
+        let ret = parse(&["1", "2", "3"])
+            .then(try_for_each!(
+                #[flatten]
+                |x| {
+                    if x == 2 {
+                        "two".parse::<i32>()?;
+                    }
+                    Ok::<_, ParseIntError>(())
+                }
+            ))
+            .await;
+        assert!(ret.is_err());
+
+        let ret = parse(&["1", "2"])
+            .then(try_for_each!(
+                #[flatten]
+                |_| Ok::<_, ParseIntError>(())
+            ))
+            .await;
+        assert_eq!(ret, Ok(2));
+
+        let ret = parse(&["1", "x"])
+            .then(try_for_each!(
+                #[flatten]
+                |_| Ok::<_, ParseIntError>(())
+            ))
+            .await;
+        assert!(ret.is_err());
+
+        let (out, ret) = parse(&["1", "2", "x"])
+            .then(try_transform_each!(
+                #[flatten]
+                |x| {
+                    next!(x * 10);
+                    Ok::<_, ParseIntError>(())
+                }
+            ))
+            .collect_with_return::<Vec<_>, _>()
+            .await;
+        assert_eq!(out, [10, 20]);
+        assert!(ret.unwrap().is_err());
+
+        let (out, ret) = flow_of![&["1"][..], &["2", "x"], &["3"]]
+            .then(try_transform_each!(|input| {
+                next_from!(parse(input))?;
+                Ok::<_, ParseIntError>(())
+            }))
+            .collect_with_return::<Vec<_>, _>()
+            .await;
+        assert_eq!(out, [1, 2]);
+        assert!(ret.unwrap().is_err());
+    });
+}
+
+#[derive(Flow)]
+enum Numbers<A, B> {
+    Listed(A),
+    Counted(B),
+}
+
+#[derive(IntoFlow)]
+enum Source<St> {
+    Listed(Vec<i32>),
+    Counted(std::ops::Range<i32>),
+    Streamed(St),
+}
+
+mod renamed {
+    use flows as flows_alias;
+
+    #[derive(flows_alias::Flow)]
+    #[flow(crate = flows_alias)]
+    pub enum Either<A, B> {
+        Left(A),
+        Right(B),
+    }
+}
+
+#[test]
+fn flow_one_of() {
+    fn pick(key: u8) -> impl Flow<Yield = i32, Return = ()> {
+        one_of!(key;
+            0 => [1, 2].into_flow(),
+            k if k < 2 => (10..13).into_flow(),
+            2 => empty(),
+            _ => successors(Some(1), |&x| (x < 8).then_some(x * 2)),
+        )
+    }
+
+    fn numbers(listed: bool) -> impl Flow<Yield = i32, Return = ()> {
+        match listed {
+            true => Numbers::Listed(flow_of![7, 8]),
+            false => Numbers::Counted((0..2).into_flow()),
+        }
+    }
+
+    fn renamed(left: bool) -> impl Flow<Yield = i32, Return = ()> {
+        match left {
+            true => renamed::Either::Left(flow_of![3, 4]),
+            false => renamed::Either::Right(empty()),
+        }
+    }
+
+    async_test(async {
+        assert_eq!(pick(0).collect::<Vec<_>, _>().await, [1, 2]);
+        assert_eq!(pick(1).collect::<Vec<_>, _>().await, [10, 11, 12]);
+        assert!(pick(2).collect::<Vec<_>, _>().await.is_empty());
+        assert_eq!(pick(3).collect::<Vec<_>, _>().await, [1, 2, 4, 8]);
+
+        assert_eq!(numbers(true).collect::<Vec<_>, _>().await, [7, 8]);
+        assert_eq!(numbers(false).collect::<Vec<_>, _>().await, [0, 1]);
+
+        assert_eq!(renamed(true).collect::<Vec<_>, _>().await, [3, 4]);
+        assert!(renamed(false).collect::<Vec<_>, _>().await.is_empty());
+
+        let sources = [
+            Source::Listed(vec![1, 2]),
+            Source::Counted(3..5),
+            Source::Streamed(futures::stream::iter(vec![5, 6])),
+        ];
+        let mut out = Vec::new();
+        for source in sources {
+            out.extend(source.into_flow().collect::<Vec<_>, _>().await);
+        }
+        assert_eq!(out, [1, 2, 3, 4, 5, 6]);
+    });
+}
+
+#[test]
+fn flow_select() {
+    async_test(async {
+        let (gate_tx, gate_rx) = futures::channel::oneshot::channel();
+        let data = flow!(move || {
+            next!(1);
+            gate_rx.await.unwrap();
+            next!(2);
+        });
+        let ticks = flow!(move || {
+            next!(10);
+            gate_tx.send(()).unwrap();
+            next!(20);
+        });
+
+        let out = flow!(move || {
+            let (data, ticks) = (pin!(data), pin!(ticks));
+            let (mut data, mut ticks) = (Selectable::new(data), Selectable::new(ticks));
+            loop {
+                select_flow! {
+                    item = data => if let Continue(x) = item {
+                        next!(x);
+                    },
+                    item = ticks => if let Continue(x) = item {
+                        next!(x);
+                    },
+                    complete => break,
+                }
+            }
+        })
+        .collect::<Vec<_>, _>()
+        .await;
+        assert_eq!(out, [1, 10, 20, 2]);
+
+        let (cancel_tx, cancel_rx) = futures::channel::oneshot::channel();
+        let data = flow!(move || -> &str {
+            next!(1);
+            cancel_tx.send(()).unwrap();
+            std::future::pending::<()>().await;
+            "unreachable"
+        });
+
+        let (out, ret) = flow!(move || {
+            let data = pin!(data);
+            let mut data = Selectable::new(data);
+            let mut cancel = cancel_rx;
+            loop {
+                select_flow! {
+                    item = data => match item {
+                        Continue(x) => next!(x),
+                        Break(ret) => break ret,
+                    },
+                    _ = cancel => break "cancelled",
+                }
+            }
+        })
+        .collect_with_return::<Vec<_>, _>()
+        .await;
+        assert_eq!(out, [1]);
+        assert_eq!(ret, Some("cancelled"));
+    });
+}
+
+#[test]
+fn flow_read() {
+    use futures::io::Cursor;
+
+    async_test(async {
+        let (out, ret) = read_chunks(Cursor::new(b"hello world"), 4)
+            .collect_with_return::<Vec<_>, _>()
+            .await;
+        assert_eq!(out, [&b"hell"[..], b"o wo", b"rld"]);
+        assert!(ret.unwrap().is_ok());
+
+        let mut chunks = read_chunks(Cursor::new(b"abcdef"), 3);
+        let Continue(first) = chunks.resume(None).await else {
+            panic!("expected a chunk");
+        };
+        assert_eq!(first, b"abc");
+        let reused = first.as_ptr();
+        let Continue(second) = chunks.resume(Some(first)).await else {
+            panic!("expected a chunk");
+        };
+        assert_eq!(second, b"def");
+        assert_eq!(second.as_ptr(), reused);
+        assert!(matches!(chunks.resume(Some(second)).await, Break(Ok(()))));
+
+        let (out, ret) = read_lines(Cursor::new("one\r\ntwo\n\nthree"))
+            .collect_with_return::<Vec<_>, _>()
+            .await;
+        assert_eq!(out, ["one", "two", "", "three"]);
+        assert!(ret.unwrap().is_ok());
+
+        let (out, ret) = read_lines(Cursor::new(b"ok\n\xff\n"))
+            .collect_with_return::<Vec<_>, _>()
+            .await;
+        assert_eq!(out, ["ok"]);
+        assert_eq!(ret.unwrap().unwrap_err().kind(), io::ErrorKind::InvalidData);
+    });
+}
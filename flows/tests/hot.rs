@@ -0,0 +1,291 @@
+mod utils;
+
+use std::{
+    cell::Cell,
+    future::Future,
+    ops::ControlFlow::{Break, Continue},
+    pin::{pin, Pin},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll, Wake, Waker},
+    time::Duration,
+};
+
+use async_io::Timer;
+
+use flows::{
+    flow, flow_of,
+    hot::{shared_from_fn, BufferOverflow, MutableSharedFlow, MutableStateFlow, SharingStarted},
+    ops::{delay_each, map_each, share_in, state_in},
+    Flow, FromFlow,
+};
+use futures::{
+    future::{select, Either},
+    join, poll,
+};
+
+use crate::utils::async_test;
+
+#[test]
+fn shared_flow_multiple_subscribers() {
+    async_test(async {
+        let flow = MutableSharedFlow::default();
+        let a = flow.subscribe();
+        let b = flow.subscribe().then(map_each!(|x| x * 10));
+
+        let producer = async move {
+            for i in 1..=5 {
+                flow.emit(i).await;
+            }
+        };
+
+        let (_, a, b) = join!(producer, Vec::from_flow(a), Vec::from_flow(b));
+
+        assert_eq!(a, [1, 2, 3, 4, 5]);
+        assert_eq!(b, [10, 20, 30, 40, 50]);
+    });
+}
+
+#[test]
+fn shared_flow_replay() {
+    async_test(async {
+        let flow = MutableSharedFlow::new(2, 1, BufferOverflow::Suspend);
+
+        for i in 1..=4 {
+            flow.emit(i).await;
+        }
+        assert_eq!(flow.replay_cache(), [3, 4]);
+
+        let early = flow.subscribe();
+        flow.emit(5).await;
+        let late = flow.subscribe();
+        drop(flow);
+
+        assert_eq!(Vec::from_flow(early).await, [3, 4, 5]);
+        assert_eq!(Vec::from_flow(late).await, [4, 5]);
+    });
+}
+
+#[test]
+fn shared_flow_overflow() {
+    async_test(async {
+        let oldest = MutableSharedFlow::new(0, 2, BufferOverflow::DropOldest);
+        let latest = MutableSharedFlow::new(0, 2, BufferOverflow::DropLatest);
+        let suspend = MutableSharedFlow::new(0, 2, BufferOverflow::Suspend);
+        let subs = (oldest.subscribe(), latest.subscribe(), suspend.subscribe());
+
+        for i in 1..=5 {
+            assert_eq!(oldest.try_emit(i), Ok(()));
+            assert_eq!(latest.try_emit(i), Ok(()));
+            assert_eq!(suspend.try_emit(i), if i <= 2 { Ok(()) } else { Err(i) });
+        }
+        drop((oldest, latest, suspend));
+
+        assert_eq!(Vec::from_flow(subs.0).await, [4, 5]);
+        assert_eq!(Vec::from_flow(subs.1).await, [1, 2]);
+        assert_eq!(Vec::from_flow(subs.2).await, [1, 2]);
+    });
+}
+
+#[test]
+fn shared_flow_rendezvous() {
+    async_test(async {
+        let flow = MutableSharedFlow::new(0, 0, BufferOverflow::Suspend);
+        let mut sub = flow.subscribe();
+
+        assert_eq!(flow.try_emit(1), Err(1));
+
+        let mut emit = flow.emit(2);
+        assert_eq!(poll!(&mut emit), Poll::Pending);
+        assert_eq!(sub.next().await, Continue(2));
+        assert_eq!(poll!(&mut emit), Poll::Ready(()));
+
+        drop(flow);
+        assert_eq!(sub.next().await, Break(()));
+    });
+}
+
+struct CountWakes(AtomicUsize);
+
+impl Wake for CountWakes {
+    fn wake(self: Arc<Self>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn shared_flow_registers_wakers_once() {
+    let wakes = Arc::new(CountWakes(AtomicUsize::new(0)));
+    let waker = Waker::from(wakes.clone());
+    let mut cx = Context::from_waker(&waker);
+
+    let flow = MutableSharedFlow::new(0, 1, BufferOverflow::Suspend);
+    let mut sub = flow.subscribe();
+    flow.try_emit(1).unwrap();
+
+    let mut emit = flow.emit(2);
+    for _ in 0..10 {
+        assert_eq!(Pin::new(&mut emit).poll(&mut cx), Poll::Pending);
+    }
+    assert_eq!(
+        Pin::new(&mut sub).poll_resume(&mut cx, &mut Some(())),
+        Poll::Ready(Continue(1))
+    );
+    assert_eq!(wakes.0.load(Ordering::SeqCst), 1);
+    assert_eq!(Pin::new(&mut emit).poll(&mut cx), Poll::Ready(()));
+
+    let mut other = flow.subscribe();
+    assert_eq!(
+        Pin::new(&mut other).poll_resume(&mut cx, &mut None),
+        Poll::Pending
+    );
+    assert_eq!(
+        Pin::new(&mut sub).poll_resume(&mut cx, &mut Some(())),
+        Poll::Ready(Continue(2))
+    );
+    flow.try_emit(3).unwrap();
+    assert_eq!(wakes.0.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn state_flow_wakes_idle_subscription() {
+    let wakes = Arc::new(CountWakes(AtomicUsize::new(0)));
+    let waker = Waker::from(wakes.clone());
+    let mut cx = Context::from_waker(&waker);
+
+    let state = MutableStateFlow::new(0);
+    let mut sub = state.subscribe();
+    assert_eq!(
+        Pin::new(&mut sub).poll_resume(&mut cx, &mut None),
+        Poll::Pending
+    );
+    state.set(1);
+    assert_eq!(wakes.0.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn state_flow_conflates() {
+    async_test(async {
+        let state = MutableStateFlow::new(0);
+        let mut sub = state.subscribe();
+
+        assert_eq!(sub.next().await, Continue(0));
+
+        state.set(1);
+        state.update(|x| x + 1);
+        assert_eq!(state.value(), 2);
+        assert_eq!(sub.next().await, Continue(2));
+
+        state.set(2);
+        drop(state);
+        assert_eq!(sub.next().await, Break(()));
+    });
+}
+
+#[test]
+fn flow_state_in() {
+    async_test(async {
+        let (state, driver) = flow_of![1, 2, 2, 3]
+            .then(delay_each(Duration::from_millis(5)))
+            .then(state_in(0));
+        let sub = state.subscribe();
+
+        let ((), out) = join!(driver, Vec::from_flow(sub));
+
+        assert_eq!(out, [0, 1, 2, 3]);
+        assert_eq!(state.value(), 3);
+    });
+}
+
+#[test]
+fn flow_share_in_lazily() {
+    async_test(async {
+        let (shared, driver) = flow_of![1, 2, 3]
+            .then(delay_each(Duration::from_millis(5)))
+            .then(share_in(SharingStarted::Lazily, 0));
+
+        let a = shared.subscribe();
+        let b = shared.subscribe();
+        let (ret, a, b) = join!(driver, Vec::from_flow(a), Vec::from_flow(b));
+
+        assert_eq!(ret, Some(()));
+        assert_eq!(a, [1, 2, 3]);
+        assert_eq!(b, [1, 2, 3]);
+    });
+}
+
+#[test]
+#[should_panic = "use `shared_from_fn`"]
+fn flow_share_in_rejects_while_subscribed() {
+    let _ = flow_of![1, 2].then(share_in(
+        SharingStarted::WhileSubscribed {
+            stop_timeout: Duration::from_millis(10),
+        },
+        0,
+    ));
+}
+
+#[test]
+fn flow_share_in_eagerly() {
+    async_test(async {
+        let (shared, driver) = flow_of![1, 2, 3].then(share_in(SharingStarted::Eagerly, 1));
+
+        assert_eq!(driver.await, Some(()));
+        assert_eq!(shared.replay_cache(), [3]);
+        assert_eq!(Vec::from_flow(shared.subscribe()).await, [3]);
+    });
+}
+
+#[test]
+fn shared_from_fn_restarts() {
+    async_test(async {
+        let starts = Cell::new(0);
+        let (shared, driver) = shared_from_fn(
+            || {
+                starts.set(starts.get() + 1);
+                flow!(|| {
+                    let mut i = 0;
+                    loop {
+                        next!(i);
+                        i += 1;
+                    }
+                })
+                .then(delay_each(Duration::from_millis(2)))
+            },
+            SharingStarted::WhileSubscribed {
+                stop_timeout: Duration::from_millis(10),
+            },
+            0,
+        );
+
+        let take_two = || async {
+            let mut sub = shared.subscribe();
+            let mut out = Vec::new();
+            while let Continue(x) = sub.next().await {
+                out.push(x);
+                if out.len() == 2 {
+                    break;
+                }
+            }
+            out
+        };
+
+        let consumer = async {
+            let first = take_two().await;
+            Timer::after(Duration::from_millis(30)).await;
+            let second = take_two().await;
+            (first, second)
+        };
+
+        match select(pin!(driver), pin!(consumer)).await {
+            Either::Right(((first, second), _)) => {
+                assert_eq!(first, [0, 1]);
+                assert_eq!(second, [0, 1]);
+            }
+            Either::Left((ret, _)) => panic!("driver finished early: {ret:?}"),
+        }
+        assert_eq!(starts.get(), 2);
+    });
+}
@@ -0,0 +1,6 @@
+use flows::{flow_of, ops::for_each, Flow};
+
+fn main() {
+    let mut out = Vec::new();
+    let _ = flow_of![1, 2].then(for_each!(#[capture(&mut out)] |x| out.push(x)));
+}
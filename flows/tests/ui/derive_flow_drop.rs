@@ -0,0 +1,12 @@
+use flows::Flow;
+
+#[derive(Flow)]
+enum Source<A> {
+    Only(A),
+}
+
+impl<A> Drop for Source<A> {
+    fn drop(&mut self) {}
+}
+
+fn main() {}
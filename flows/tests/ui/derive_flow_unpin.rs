@@ -0,0 +1,10 @@
+use flows::Flow;
+
+#[derive(Flow)]
+enum Source<A> {
+    Only(A),
+}
+
+impl<A> Unpin for Source<A> {}
+
+fn main() {}
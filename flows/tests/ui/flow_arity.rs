@@ -0,0 +1,5 @@
+use flows::flow;
+
+fn main() {
+    let _ = flow!(|first, sender, extra| {});
+}
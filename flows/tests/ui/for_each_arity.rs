@@ -0,0 +1,5 @@
+use flows::{flow_of, ops::for_each, Flow};
+
+fn main() {
+    let _ = flow_of![1, 2].then(for_each!(|a, b| a + b));
+}
@@ -0,0 +1,10 @@
+use flows::{flow_of, ops::map_concurrent, Flow};
+
+fn main() {
+    let mut total = 0;
+    let _ = flow_of![1, 2].then(map_concurrent!(
+        2,
+        #[capture(ref mut total)]
+        |x| *total += x
+    ));
+}
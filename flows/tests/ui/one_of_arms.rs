@@ -0,0 +1,21 @@
+use flows::{one_of, Flow};
+
+fn two(key: u8) -> impl Flow<Yield = i32, Return = ()> {
+    one_of!(key; 0 => flows::empty(), _ => flows::empty())
+}
+
+fn nine(key: u8) -> impl Flow<Yield = i32, Return = ()> {
+    one_of!(key;
+        0 => flows::empty(),
+        1 => flows::empty(),
+        2 => flows::empty(),
+        3 => flows::empty(),
+        4 => flows::empty(),
+        5 => flows::empty(),
+        6 => flows::empty(),
+        7 => flows::empty(),
+        _ => flows::empty(),
+    )
+}
+
+fn main() {}
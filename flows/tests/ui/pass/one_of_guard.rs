@@ -0,0 +1,14 @@
+use flows::{empty, flow_of, one_of, Flow, IntoFlow};
+
+fn pick(key: u8) -> impl Flow<Yield = i32, Return = ()> {
+    one_of!(key;
+        0 => empty(),
+        n if n < 10 => flow_of![1, 2],
+        11 | 12 if key % 2 == 0 => (0..3).into_flow(),
+        _ => [4].into_flow(),
+    )
+}
+
+fn main() {
+    let _ = pick(3);
+}
@@ -0,0 +1,5 @@
+use flows::{flow_of, ops::transform_each, Flow};
+
+fn main() {
+    let _ = flow_of![1, 2].then(transform_each!(|| next!(1)));
+}
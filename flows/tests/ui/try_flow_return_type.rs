@@ -0,0 +1,7 @@
+use flows::try_flow;
+
+fn main() {
+    let _ = try_flow!(|| {
+        next!("1".parse::<i32>()?);
+    });
+}
@@ -0,0 +1,6 @@
+use flows::flow;
+
+fn main() {
+    let name = String::new();
+    let _ = flow!(#[captures(name)] || next!(name));
+}
@@ -9,7 +9,7 @@ pub async fn timeout<T>(ms: impl Into<Option<u64>>, block: impl Future<Output =
     let mut timer = Timer::after(Duration::from_millis(ms)).fuse();
 
     select! {
-        out = block => return out,
+        out = block => out,
         _ = timer => panic!("timeout exceeded"),
     }
 }